@@ -1,11 +1,9 @@
 #[macro_use]
 extern crate libcodejam;
 
+use libcodejam::cli::*;
 use libcodejam::data::*;
-use libcodejam::executor::*;
-use libcodejam::printer::*;
 use libcodejam::solver::*;
-use libcodejam::tokens::*;
 
 use std::collections::HashMap;
 
@@ -16,11 +14,7 @@ struct_group!{
 }}
 
 fn main() {
-    ThreadExecutor::run(
-        TokensReader::stdin(),
-        StandardPrinter::stdout(),
-        solver(|_data: Data| {
-            "Done"
-        }),
-    );
+    run_cli(solver(|_data: Data| {
+        "Done"
+    }));
 }
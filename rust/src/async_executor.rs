@@ -0,0 +1,142 @@
+use std::fmt::Display;
+use std::process::exit;
+use std::time::Instant;
+
+use futures::executor::block_on;
+use futures::io::AsyncBufRead;
+
+use crate::async_tokens::AsyncTokensReader;
+use crate::data::{GlobalData, Group, LoadGlobalData};
+use crate::executor::{CaseError, ExecutionError, ExecutorSettings, Settings, SolverError};
+use crate::monitor::Monitor;
+use crate::printer::Printer;
+use crate::solver::Solver;
+
+type CaseDataError<S> = <<S as Solver>::CaseData as Group>::Err;
+
+/// An executor for input from an `AsyncTokensReader`. Each case is loaded
+/// asynchronously, then solved and printed in the executor's task, the same
+/// way as with a `SequentialExecutor`. Since the solver blocks the task while
+/// it runs, the future should be driven somewhere that can afford to block,
+/// such as a dedicated thread with `run`, or a runtime's blocking pool.
+///
+/// It doesn't implement `Executor`, whose input is synchronous, but its
+/// settings are set through `ExecutorSettings` like any other executor's.
+#[derive(Debug, Clone, Default)]
+pub struct AsyncExecutor<M = ()> {
+    monitor: M,
+    settings: Settings,
+}
+
+impl AsyncExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M> AsyncExecutor<M> {
+    /// Report the progress of each run to a monitor
+    pub fn monitor<M2: Monitor>(self, monitor: M2) -> AsyncExecutor<M2> {
+        AsyncExecutor {
+            monitor,
+            settings: self.settings,
+        }
+    }
+}
+
+impl<M> ExecutorSettings for AsyncExecutor<M> {
+    fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+}
+
+impl<M: Monitor> AsyncExecutor<M> {
+    async fn execute_cases<R, P, S>(
+        &self,
+        global_data: &GlobalData<S::GlobalData>,
+        tokens: &mut AsyncTokensReader<R>,
+        mut printer: P,
+        solver: S,
+    ) -> Result<(), CaseError<CaseDataError<S>>>
+        where
+            R: AsyncBufRead + Unpin,
+            P: Printer,
+            S: Solver,
+            S::CaseData: Group,
+            S::Solution: Display,
+    {
+        let monitor = &self.monitor;
+        let settings = &self.settings;
+
+        for (case, _) in global_data.cases() {
+            let start = Instant::now();
+            let case_data = tokens
+                .next::<S::CaseData>()
+                .await
+                .map_err(|err| CaseError::load_error(case, err))?;
+            let load_time = start.elapsed();
+
+            if !settings.selection.contains(case) {
+                continue;
+            }
+
+            if let Some(solution) = settings.replay(case) {
+                printer
+                    .print_solution(case, solution)
+                    .map_err(|err| CaseError::print_error(case, err))?;
+                continue;
+            }
+
+            monitor.case_loaded(case, load_time);
+
+            let solution = settings.solve(&solver, case, &global_data.data, case_data, monitor);
+
+            settings
+                .print(&mut printer, case, solution)
+                .map_err(|err| err.into_case_error(case))?;
+            monitor.case_printed(case);
+        }
+
+        Ok(())
+    }
+
+    /// Solve and print every case in a problem
+    pub async fn execute<R, P, S>(
+        &self,
+        mut tokens: AsyncTokensReader<R>,
+        printer: P,
+        solver: S,
+    ) -> Result<(), SolverError<S>>
+        where
+            R: AsyncBufRead + Unpin,
+            P: Printer,
+            S: Solver,
+            S::GlobalData: LoadGlobalData,
+            S::CaseData: Group,
+            S::Solution: Display,
+    {
+        let global_data = tokens.start_problem().await?;
+
+        self.monitor.start(self.settings.count_pending(global_data.num_cases));
+        let result = self.execute_cases(&global_data, &mut tokens, printer, solver).await;
+        self.monitor.finish();
+
+        result.map_err(ExecutionError::Case)
+    }
+
+    /// Block on `execute`, printing any error and exiting
+    pub fn run<R, P, S>(&self, tokens: AsyncTokensReader<R>, printer: P, solver: S)
+        where
+            R: AsyncBufRead + Unpin,
+            P: Printer,
+            S: Solver,
+            S::GlobalData: LoadGlobalData,
+            S::CaseData: Group,
+            S::Solution: Display,
+    {
+        block_on(self.execute(tokens, printer, solver)).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        })
+    }
+}
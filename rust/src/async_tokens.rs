@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+use std::io::ErrorKind::Interrupted;
+use std::str::from_utf8;
+
+use futures::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::data::{GlobalData, GlobalDataError, Group, LoadGlobalData};
+use crate::tokens::{LoadError, Tokens};
+
+// The tokens which have been read so far, lent to a synchronous loader
+struct BufferedTokens<'a> {
+    tokens: &'a VecDeque<String>,
+    consumed: usize,
+    // Whether the loader needed a token which hasn't been read yet
+    starved: bool,
+    finished: bool,
+    error: &'a mut Option<LoadError>,
+}
+
+impl<'a> Tokens for BufferedTokens<'a> {
+    fn next_raw(&mut self) -> Result<&str, LoadError> {
+        match self.tokens.get(self.consumed) {
+            Some(token) => {
+                self.consumed += 1;
+                Ok(token)
+            }
+            None if !self.finished => {
+                self.starved = true;
+                Err(LoadError::OutOfTokens)
+            }
+            None => Err(self.error.take().unwrap_or(LoadError::OutOfTokens)),
+        }
+    }
+}
+
+/// The asynchronous equivalent of `TokensReader`, for input from an
+/// `AsyncBufRead`. Groups are loaded with their usual `Group` implementations
+/// and produce the same errors.
+///
+/// Groups are loaded synchronously from the tokens read so far. When a group
+/// needs more tokens than that, lines are read until the buffered tokens
+/// have doubled, and the group is loaded again from its first token. Each
+/// token is parsed at most a few times on average, however many lines its
+/// group is spread over. Because input is read ahead of the group being
+/// loaded, this isn't suitable for interactive problems.
+pub struct AsyncTokensReader<R: AsyncBufRead + Unpin> {
+    reader: R,
+    line: Vec<u8>,
+    tokens: VecDeque<String>,
+    finished: bool,
+    // An error which ended the input; it's reported by whatever next runs
+    // out of tokens
+    error: Option<LoadError>,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncTokensReader<R> {
+    pub fn new(reader: R) -> Self {
+        AsyncTokensReader {
+            reader,
+            line: Vec::with_capacity(1024),
+            tokens: VecDeque::new(),
+            finished: false,
+            error: None,
+        }
+    }
+
+    // Read the tokens on the next line of input
+    async fn read_line(&mut self) {
+        self.line.clear();
+
+        let result = loop {
+            match self.reader.read_until(b'\n', &mut self.line).await {
+                Err(ref err) if err.kind() == Interrupted => continue,
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(0) => self.finished = true,
+            Ok(_) => match from_utf8(&self.line) {
+                Ok(line) => self.tokens.extend(line.split_ascii_whitespace().map(String::from)),
+                Err(err) => {
+                    self.finished = true;
+                    self.error = Some(LoadError::Utf8Error(err));
+                }
+            },
+            Err(err) => {
+                self.finished = true;
+                self.error = Some(LoadError::Io(err));
+            }
+        }
+    }
+
+    // Read lines until `count` more tokens are buffered, or the input ends
+    async fn read_tokens(&mut self, count: usize) {
+        let target = self.tokens.len() + count;
+
+        while self.tokens.len() < target && !self.finished {
+            self.read_line().await;
+        }
+    }
+
+    // Run a synchronous loader over the buffered tokens, reading more input
+    // until it succeeds, fails for some other reason, or the input ends.
+    async fn load<T, E>(&mut self, load: impl Fn(&mut BufferedTokens) -> Result<T, E>) -> Result<T, E> {
+        loop {
+            let mut tokens = BufferedTokens {
+                tokens: &self.tokens,
+                consumed: 0,
+                starved: false,
+                finished: self.finished,
+                error: &mut self.error,
+            };
+
+            let result = load(&mut tokens);
+            let BufferedTokens { consumed, starved, .. } = tokens;
+
+            match result {
+                // Even a successful load is retried, since the loader may
+                // have treated the end of the buffer as the end of input
+                _ if starved => self.read_tokens(self.tokens.len().max(1)).await,
+                result => {
+                    self.tokens.drain(..consumed);
+                    return result;
+                }
+            }
+        }
+    }
+
+    pub async fn next_raw(&mut self) -> Result<String, LoadError> {
+        loop {
+            if let Some(token) = self.tokens.pop_front() {
+                return Ok(token);
+            }
+
+            if self.finished {
+                return Err(self.error.take().unwrap_or(LoadError::OutOfTokens));
+            }
+
+            self.read_line().await;
+        }
+    }
+
+    pub async fn next<T: Group>(&mut self) -> Result<T, T::Err> {
+        self.load(|tokens| tokens.next()).await
+    }
+
+    pub async fn start_problem<T: LoadGlobalData>(&mut self) -> Result<GlobalData<T>, GlobalDataError<T::Err>> {
+        self.load(|tokens| tokens.start_problem()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures::executor::block_on;
+
+    use super::*;
+
+    thread_local! {
+        static TOKENS_READ: Cell<usize> = Cell::new(0);
+    }
+
+    // A group of every remaining token, counting how many are read by each load
+    #[derive(Debug)]
+    struct Everything(Vec<String>);
+
+    impl Group for Everything {
+        type Err = LoadError;
+
+        fn from_tokens(tokens: &mut impl Tokens) -> Result<Self, LoadError> {
+            let mut all = Vec::new();
+
+            loop {
+                match tokens.next_raw() {
+                    Ok(token) => {
+                        TOKENS_READ.with(|count| count.set(count.get() + 1));
+                        all.push(token.to_string());
+                    }
+                    Err(LoadError::OutOfTokens) => return Ok(Everything(all)),
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn group_over_many_lines_is_read_in_linear_time() {
+        let lines = 1000;
+        let input: String = (0..lines).map(|line| format!("{}\n", line)).collect();
+
+        TOKENS_READ.with(|count| count.set(0));
+        let mut tokens = AsyncTokensReader::new(input.as_bytes());
+        let Everything(all) = block_on(tokens.next()).unwrap();
+
+        assert_eq!(all.len(), lines);
+        assert_eq!(all[lines - 1], "999");
+        assert!(TOKENS_READ.with(Cell::get) <= 4 * lines);
+    }
+
+    #[test]
+    fn tokens_are_read_across_lines() {
+        let mut tokens = AsyncTokensReader::new("2\n3 4\n\n 5\n".as_bytes());
+
+        assert_eq!(block_on(tokens.next::<u32>()).unwrap(), 2);
+        assert_eq!(block_on(tokens.next::<(u32, u32, u32)>()).unwrap(), (3, 4, 5));
+        assert!(block_on(tokens.next::<u32>()).is_err());
+    }
+}
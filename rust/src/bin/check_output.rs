@@ -0,0 +1,64 @@
+// Compare a solution's output file against the expected output, allowing
+// floating point answers to differ within a tolerance.
+//
+// Usage: check_output ACTUAL EXPECTED [--abs TOLERANCE] [--rel TOLERANCE]
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process::exit;
+
+use libcodejam::checker::{check_output, Tolerance};
+use libcodejam::tokens::TokensReader;
+
+const USAGE: &str = "usage: check_output ACTUAL EXPECTED [--abs TOLERANCE] [--rel TOLERANCE]";
+
+fn fail(message: impl AsRef<str>) -> ! {
+    eprintln!("{}", message.as_ref());
+    exit(2)
+}
+
+fn open(path: &str) -> TokensReader<BufReader<File>> {
+    match File::open(path) {
+        Ok(file) => TokensReader::new(BufReader::new(file)),
+        Err(err) => fail(format!("couldn't open {}: {}", path, err)),
+    }
+}
+
+fn main() {
+    let mut paths = Vec::new();
+    let mut tolerance = Tolerance::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let field = match arg.as_str() {
+            "--abs" => &mut tolerance.absolute,
+            "--rel" => &mut tolerance.relative,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => {
+                paths.push(arg);
+                continue;
+            }
+        };
+
+        *field = match args.next().map(|value| value.parse()) {
+            Some(Ok(value)) => value,
+            _ => fail(USAGE),
+        };
+    }
+
+    if paths.len() != 2 {
+        fail(USAGE);
+    }
+
+    match check_output(&mut open(&paths[0]), &mut open(&paths[1]), tolerance) {
+        Ok(()) => println!("OK"),
+        Err(err) => {
+            println!("{}", err);
+            exit(1)
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// Write a new input file containing only one case of an input file, with a
+// case count of 1 and the original global data, so that the case can be
+// reproduced on its own. Cases are found by their line layout, so this works
+// for any problem; a solution can instead call `extract::extract_cli` with
+// its own types.
+//
+// Usage: extract INPUT CASE [OUTPUT] [--lines N | --counted]
+//                [--global-before N] [--global-after N]
+
+use std::env;
+use std::fs;
+use std::process::exit;
+
+use libcodejam::case_index::CaseIndex;
+use libcodejam::extract::{extract_case_lines, CaseLines, LineLayout};
+
+const USAGE: &str = "\
+usage: extract INPUT CASE [OUTPUT] [OPTIONS]
+
+Writes case CASE of INPUT to OUTPUT (default stdout) as an input file of its
+own. The case count must be the first token of its line.
+
+options:
+    --lines N           every case is N lines (default 1)
+    --counted           every case starts with a line whose first token is
+                        the number of lines which follow it
+    --global-before N   N lines of global data come before the case count
+    --global-after N    N lines of global data come after the case count
+";
+
+fn fail(message: impl AsRef<str>) -> ! {
+    eprintln!("{}", message.as_ref());
+    exit(2)
+}
+
+fn main() {
+    let mut positional = Vec::new();
+    let mut layout = LineLayout::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let mut count = || match args.next().map(|value| value.parse()) {
+            Some(Ok(value)) => value,
+            _ => fail(format!("{} needs a line count\n\n{}", arg, USAGE)),
+        };
+
+        match arg.as_str() {
+            "--lines" => layout.case_lines = CaseLines::Fixed(count()),
+            "--counted" => layout.case_lines = CaseLines::Counted,
+            "--global-before" => layout.global_before = count(),
+            "--global-after" => layout.global_after = count(),
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return;
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    if positional.len() < 2 || positional.len() > 3 {
+        fail(USAGE);
+    }
+
+    let case = match positional[1].parse().ok().and_then(CaseIndex::new) {
+        Some(case) => case,
+        None => fail(format!("invalid case number: {}\n\n{}", positional[1], USAGE)),
+    };
+
+    let input = fs::read_to_string(&positional[0])
+        .unwrap_or_else(|err| fail(format!("couldn't read {}: {}", positional[0], err)));
+
+    let text = extract_case_lines(&input, layout, case).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1)
+    });
+
+    match positional.get(2) {
+        None => print!("{}", text),
+        Some(path) => fs::write(path, text).unwrap_or_else(|err| {
+            eprintln!("couldn't write {}: {}", path, err);
+            exit(1)
+        }),
+    }
+}
@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, Instant};
+
+use crate::context;
+
+/// Returned by `Cancellation::check` once a case has run out of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "case ran out of time")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// A cooperative cancellation token for a case with a time limit. Executors
+/// can't interrupt a running solver, so long-running solvers should check
+/// the token periodically and give up once it's cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cancellation {
+    deadline: Option<Instant>,
+}
+
+impl Cancellation {
+    /// A token which is never cancelled
+    pub fn never() -> Self {
+        Cancellation { deadline: None }
+    }
+
+    pub fn at(deadline: Instant) -> Self {
+        Cancellation {
+            deadline: Some(deadline),
+        }
+    }
+
+    /// The token for the case being solved on this thread. Outside of a case,
+    /// or in a case without a time limit, it is never cancelled.
+    pub fn current() -> Self {
+        Cancellation {
+            deadline: context::current().and_then(|context| context.deadline),
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The time left before cancellation, or `None` if there's no deadline
+    pub fn remaining(&self) -> Option<Duration> {
+        let now = Instant::now();
+
+        self.deadline.map(|deadline| {
+            if deadline > now {
+                deadline - now
+            } else {
+                Duration::default()
+            }
+        })
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+
+    /// Return an error if the token is cancelled, for use with `?`
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for Cancellation {
+    fn default() -> Self {
+        Cancellation::never()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{Executor, ExecutorSettings, SequentialExecutor};
+    use crate::printer::CollectingPrinter;
+    use crate::solver::solver;
+    use crate::tokens::TokensReader;
+
+    // Spin until the case is cancelled, or give up after a second
+    fn wait_for_cancellation(_n: u32) -> &'static str {
+        let token = Cancellation::current();
+        let start = Instant::now();
+
+        while start.elapsed() < Duration::from_secs(1) {
+            if token.check() == Err(Cancelled) {
+                return "cancelled";
+            }
+        }
+
+        "finished"
+    }
+
+    #[test]
+    fn solver_sees_its_deadline_expire() {
+        let mut printer = CollectingPrinter::new();
+
+        SequentialExecutor::new()
+            .time_limit(Duration::from_millis(20))
+            .run(TokensReader::new("2 1 2".as_bytes()), &mut printer, solver(wait_for_cancellation));
+
+        let solutions: Vec<&str> = printer.solutions().iter().map(|(_, solution)| solution.as_str()).collect();
+        assert_eq!(solutions, ["cancelled", "cancelled"]);
+    }
+
+    #[test]
+    fn cases_without_a_time_limit_are_never_cancelled() {
+        let deadlines = solver(|_n: u32| Cancellation::current().deadline().is_some());
+        let mut printer = CollectingPrinter::new();
+
+        SequentialExecutor::new().run(TokensReader::new("1 1".as_bytes()), &mut printer, deadlines);
+
+        assert_eq!(printer.solutions()[0].1, "false");
+        assert_eq!(Cancellation::current(), Cancellation::never());
+    }
+
+    #[test]
+    fn remaining_time_stops_at_zero() {
+        let past = Cancellation::at(Instant::now() - Duration::from_millis(5));
+        let future = Cancellation::at(Instant::now() + Duration::from_secs(60));
+
+        assert!(past.is_cancelled());
+        assert_eq!(past.remaining(), Some(Duration::default()));
+        assert!(!future.is_cancelled());
+        assert!(future.remaining().unwrap() > Duration::from_secs(59));
+        assert_eq!(Cancellation::never().remaining(), None);
+    }
+}
@@ -11,6 +11,19 @@ impl CaseIndex {
     pub fn start() -> CaseIndex {
         CaseIndex(1)
     }
+
+    /// The case with a 1-based case number, or `None` for 0
+    pub fn new(number: usize) -> Option<CaseIndex> {
+        match number {
+            0 => None,
+            number => Some(CaseIndex(number)),
+        }
+    }
+
+    /// The 1-based case number
+    pub fn number(self) -> usize {
+        self.0
+    }
 }
 
 impl Default for CaseIndex {
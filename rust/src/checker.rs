@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use derive_more::From;
+
+use crate::tokens::{LoadError, Tokens};
+
+/// How far apart two numeric tokens may be and still be considered equal. A
+/// pair of tokens matches if it is within either the absolute or the relative
+/// tolerance. Tokens which are both integers are always compared exactly, by
+/// value, so `007` matches `7`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl Tolerance {
+    pub fn new(absolute: f64, relative: f64) -> Self {
+        Tolerance { absolute, relative }
+    }
+
+    pub fn exact() -> Self {
+        Tolerance::new(0.0, 0.0)
+    }
+
+    pub fn tokens_match(&self, expected: &str, actual: &str) -> bool {
+        if expected == actual {
+            return true;
+        }
+
+        if let (Ok(expected), Ok(actual)) = (expected.parse::<i128>(), actual.parse::<i128>()) {
+            return expected == actual;
+        }
+
+        match (expected.parse::<f64>(), actual.parse::<f64>()) {
+            (Ok(expected), Ok(actual)) if expected.is_finite() && actual.is_finite() => {
+                let difference = (expected - actual).abs();
+                difference <= self.absolute || difference <= self.relative * expected.abs()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The common Code Jam tolerance of 1e-6, absolute or relative
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance::new(1e-6, 1e-6)
+    }
+}
+
+/// The tokens of a single `Case #x:` in an output file. The label is `None`
+/// for any tokens appearing before the first case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputCase {
+    pub label: Option<String>,
+    pub tokens: Vec<String>,
+}
+
+fn describe(label: &Option<String>) -> &str {
+    match label {
+        Some(label) => label,
+        None => "text before the first case",
+    }
+}
+
+/// Split the tokens of an output file into cases
+pub fn read_cases(tokens: &mut impl Tokens) -> Result<Vec<OutputCase>, LoadError> {
+    let mut words = Vec::new();
+
+    loop {
+        match tokens.next_raw() {
+            Ok(word) => words.push(word.to_string()),
+            Err(LoadError::OutOfTokens) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut cases: Vec<OutputCase> = Vec::new();
+    let mut words = words.into_iter().peekable();
+
+    while let Some(word) = words.next() {
+        let is_label = word == "Case" && match words.peek() {
+            Some(number) => number.starts_with('#') && number.ends_with(':'),
+            None => false,
+        };
+
+        if is_label {
+            let number = words.next().unwrap();
+            cases.push(OutputCase {
+                label: Some(format!("Case {}", number.trim_end_matches(':'))),
+                tokens: Vec::new(),
+            });
+        } else {
+            match cases.last_mut() {
+                Some(case) => case.tokens.push(word),
+                None => cases.push(OutputCase {
+                    label: None,
+                    tokens: vec![word],
+                }),
+            }
+        }
+    }
+
+    Ok(cases)
+}
+
+/// The first place where the actual output differs from the expected output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    MissingCase(Option<String>),
+    ExtraCase(Option<String>),
+    Label {
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+    /// A token that differs within a case. `index` counts from 1, and a
+    /// token of `None` means that side of the case ended early.
+    Token {
+        case: Option<String>,
+        index: usize,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fn token(token: &Option<String>) -> String {
+            match token {
+                Some(token) => format!("`{}`", token),
+                None => "end of case".to_string(),
+            }
+        }
+
+        match self {
+            Difference::MissingCase(label) => write!(f, "{}: missing from output", describe(label)),
+            Difference::ExtraCase(label) => write!(f, "{}: unexpected extra case", describe(label)),
+            Difference::Label { expected, actual } => write!(
+                f,
+                "expected {}, found {}",
+                describe(expected),
+                describe(actual)
+            ),
+            Difference::Token {
+                case,
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}, token {}: expected {}, found {}",
+                describe(case),
+                index,
+                token(expected),
+                token(actual)
+            ),
+        }
+    }
+}
+
+/// Find the first difference between two sets of output cases
+pub fn first_difference(
+    actual: &[OutputCase],
+    expected: &[OutputCase],
+    tolerance: Tolerance,
+) -> Option<Difference> {
+    for (index, expected_case) in expected.iter().enumerate() {
+        let actual_case = match actual.get(index) {
+            Some(case) => case,
+            None => return Some(Difference::MissingCase(expected_case.label.clone())),
+        };
+
+        if actual_case.label != expected_case.label {
+            return Some(Difference::Label {
+                expected: expected_case.label.clone(),
+                actual: actual_case.label.clone(),
+            });
+        }
+
+        let length = expected_case.tokens.len().max(actual_case.tokens.len());
+
+        for token in 0..length {
+            let expected_token = expected_case.tokens.get(token);
+            let actual_token = actual_case.tokens.get(token);
+
+            let matches = match (expected_token, actual_token) {
+                (Some(expected), Some(actual)) => tolerance.tokens_match(expected, actual),
+                _ => false,
+            };
+
+            if !matches {
+                return Some(Difference::Token {
+                    case: expected_case.label.clone(),
+                    index: token + 1,
+                    expected: expected_token.cloned(),
+                    actual: actual_token.cloned(),
+                });
+            }
+        }
+    }
+
+    actual
+        .get(expected.len())
+        .map(|extra| Difference::ExtraCase(extra.label.clone()))
+}
+
+#[derive(Debug, From)]
+pub enum CheckError {
+    Load(LoadError),
+    Difference(Difference),
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CheckError::Load(err) => write!(f, "error reading output: {}", err),
+            CheckError::Difference(difference) => write!(f, "{}", difference),
+        }
+    }
+}
+
+impl Error for CheckError {
+    fn cause(&self) -> Option<&dyn Error> {
+        match self {
+            CheckError::Load(err) => Some(err),
+            CheckError::Difference(..) => None,
+        }
+    }
+}
+
+/// Check an output file against the expected output, token by token
+pub fn check_output(
+    actual: &mut impl Tokens,
+    expected: &mut impl Tokens,
+    tolerance: Tolerance,
+) -> Result<(), CheckError> {
+    let actual = read_cases(actual)?;
+    let expected = read_cases(expected)?;
+
+    match first_difference(&actual, &expected, tolerance) {
+        Some(difference) => Err(difference.into()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_compare_by_value() {
+        let tolerance = Tolerance::default();
+
+        assert!(tolerance.tokens_match("0", "-0"));
+        assert!(tolerance.tokens_match("7", "007"));
+        assert!(tolerance.tokens_match("5", "+5"));
+        assert!(!tolerance.tokens_match("1000000000000", "1000000000001"));
+    }
+
+    #[test]
+    fn floats_compare_within_tolerance() {
+        let tolerance = Tolerance::default();
+
+        assert!(tolerance.tokens_match("1.0000001", "1.0"));
+        assert!(tolerance.tokens_match("2", "2.0000000001"));
+        assert!(!tolerance.tokens_match("1.0", "1.001"));
+        assert!(!Tolerance::exact().tokens_match("1.0000001", "1.0"));
+    }
+}
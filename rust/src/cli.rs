@@ -0,0 +1,464 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::data::{Group, LoadGlobalData};
+use crate::debug::set_debug;
+use crate::executor::{Executor, ExecutorSettings, SequentialExecutor, SolverError, ThreadExecutor};
+use crate::journal::Journal;
+use crate::monitor::Monitor;
+use crate::printer::{Template, TemplatePrinter};
+use crate::progress::Progress;
+#[cfg(feature = "rayon")]
+use crate::rayon_executor::RayonExecutor;
+use crate::selection::{CaseSelection, ParseSelectionError};
+use crate::timing::Timings;
+use crate::solver::Solver;
+use crate::tokens::TokensReader;
+
+const USAGE: &str = "\
+usage: SOLUTION [OPTIONS] [INPUT [OUTPUT]]
+
+Reads the problem from INPUT (default stdin) and writes the solutions to
+OUTPUT (default stdout). A path of - means stdin or stdout.
+
+options:
+    -i, --input PATH        read the problem from PATH
+    -o, --output PATH       write the solutions to PATH
+    -e, --executor KIND     sequential, threads, or rayon if built with the
+                            rayon feature (default threads)
+    -j, --threads N         number of worker threads (implies --executor threads)
+    --reorder-window N      most cases in progress at once with threads
+    -t, --template TEMPLATE output layout, like \"Case #{case}:{sep}{solution}\"
+    --precision N           write floats in solutions with N decimal places
+    -c, --cases CASES       only solve some cases, like 7 or 1,4,10-12
+    -p, --progress          show progress on stderr
+    --no-debug              disable debug! and trace! output
+    --trace CASES           enable trace! output for some cases
+    --timing                print a summary of case timings on stderr
+    --timing-file PATH      write the timing of every case to PATH as CSV
+    --time-limit SECONDS    time limit for each case; overruns are warned
+                            about and marked in the timing report
+    --journal PATH          record solutions in PATH, and skip the cases
+                            already recorded there by an earlier run
+    -h, --help              print this message
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutorChoice {
+    Sequential,
+    /// A `ThreadExecutor`, with one thread per CPU if the count is `None`
+    Threads(Option<usize>),
+    /// A `RayonExecutor`, using the global rayon pool
+    #[cfg(feature = "rayon")]
+    Rayon,
+}
+
+/// The settings of a solution run, as parsed from the command line
+#[derive(Debug, Clone)]
+pub struct CliOptions {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub executor: ExecutorChoice,
+    pub reorder_window: Option<usize>,
+    pub template: Template,
+    pub precision: Option<usize>,
+    pub cases: CaseSelection,
+    pub debug: bool,
+    pub trace: Option<CaseSelection>,
+    pub progress: bool,
+    pub timing: bool,
+    pub timing_file: Option<PathBuf>,
+    pub time_limit: Option<Duration>,
+    pub journal: Option<PathBuf>,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            input: None,
+            output: None,
+            executor: ExecutorChoice::Threads(None),
+            reorder_window: None,
+            template: Template::default(),
+            precision: None,
+            cases: CaseSelection::All,
+            debug: true,
+            trace: None,
+            progress: false,
+            timing: false,
+            timing_file: None,
+            time_limit: None,
+            journal: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// `--help` was given
+    Help,
+    Invalid(String),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CliError::Help => f.write_str(USAGE),
+            CliError::Invalid(message) => write!(f, "{}\n\n{}", message, USAGE),
+        }
+    }
+}
+
+fn invalid(message: String) -> CliError {
+    CliError::Invalid(message)
+}
+
+fn parse_seconds(arg: &str) -> Option<Duration> {
+    let seconds: f64 = arg.parse().ok()?;
+
+    // This also rejects NaN and infinities, along with limits too long for
+    // `Duration::from_secs_f64` to hold
+    if (0.0..1e18).contains(&seconds) {
+        Some(Duration::from_secs_f64(seconds))
+    } else {
+        None
+    }
+}
+
+// `-` is the conventional name for stdin and stdout
+fn path_arg(arg: String) -> Option<PathBuf> {
+    if arg == "-" {
+        None
+    } else {
+        Some(PathBuf::from(arg))
+    }
+}
+
+impl CliOptions {
+    /// Parse options from command line arguments, not including the program
+    /// name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut options = CliOptions::default();
+        let mut positional = Vec::new();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| invalid(format!("missing value for {}", name)))
+            };
+
+            match arg.as_str() {
+                "-i" | "--input" => options.input = path_arg(value(&arg)?),
+                "-o" | "--output" => options.output = path_arg(value(&arg)?),
+                "-e" | "--executor" => {
+                    options.executor = match value(&arg)?.as_str() {
+                        "sequential" => ExecutorChoice::Sequential,
+                        "threads" => ExecutorChoice::Threads(None),
+                        #[cfg(feature = "rayon")]
+                        "rayon" => ExecutorChoice::Rayon,
+                        kind => return Err(invalid(format!("unknown executor: {}", kind))),
+                    }
+                }
+                "-j" | "--threads" => {
+                    let threads = value(&arg)?;
+                    let threads = threads
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid thread count: {}", threads)))?;
+                    options.executor = ExecutorChoice::Threads(Some(threads));
+                }
+                "--reorder-window" => {
+                    let window = value(&arg)?;
+                    options.reorder_window = Some(
+                        window
+                            .parse()
+                            .map_err(|_| invalid(format!("invalid reorder window: {}", window)))?,
+                    );
+                }
+                "-t" | "--template" => {
+                    let template = value(&arg)?;
+                    options.template = template
+                        .parse()
+                        .map_err(|err| invalid(format!("invalid template: {}", err)))?;
+                }
+                "--precision" => {
+                    let precision = value(&arg)?;
+                    options.precision = Some(
+                        precision
+                            .parse()
+                            .map_err(|_| invalid(format!("invalid precision: {}", precision)))?,
+                    );
+                }
+                "-c" | "--cases" => {
+                    options.cases = value(&arg)?
+                        .parse()
+                        .map_err(|err: ParseSelectionError| invalid(err.to_string()))?;
+                }
+                "--no-debug" => options.debug = false,
+                "--trace" => {
+                    options.trace = Some(
+                        value(&arg)?
+                            .parse()
+                            .map_err(|err: ParseSelectionError| invalid(err.to_string()))?,
+                    );
+                }
+                "-p" | "--progress" => options.progress = true,
+                "--timing" => options.timing = true,
+                "--timing-file" => options.timing_file = Some(PathBuf::from(value(&arg)?)),
+                "--time-limit" => {
+                    let limit = value(&arg)?;
+                    options.time_limit = Some(
+                        parse_seconds(&limit)
+                            .ok_or_else(|| invalid(format!("invalid time limit: {}", limit)))?,
+                    );
+                }
+                "--journal" => options.journal = Some(PathBuf::from(value(&arg)?)),
+                "-h" | "--help" => return Err(CliError::Help),
+                flag if flag.starts_with('-') && flag != "-" => {
+                    return Err(invalid(format!("unknown option: {}", flag)))
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+
+        if let Some(input) = positional.next() {
+            options.input = path_arg(input);
+        }
+
+        if let Some(output) = positional.next() {
+            options.output = path_arg(output);
+        }
+
+        match positional.next() {
+            Some(extra) => Err(invalid(format!("unexpected argument: {}", extra))),
+            None => Ok(options),
+        }
+    }
+
+    /// Parse options from the arguments of this process, printing the usage
+    /// and exiting if they are invalid or `--help` was given.
+    pub fn from_env() -> Self {
+        CliOptions::parse(std::env::args().skip(1)).unwrap_or_else(|err| match err {
+            CliError::Help => {
+                print!("{}", err);
+                exit(0);
+            }
+            CliError::Invalid(..) => {
+                eprint!("{}", err);
+                exit(2);
+            }
+        })
+    }
+
+    fn open_input(&self) -> io::Result<Box<dyn BufRead>> {
+        Ok(match self.input {
+            Some(ref path) => Box::new(BufReader::new(File::open(path)?)),
+            None => Box::new(BufReader::new(io::stdin())),
+        })
+    }
+
+    fn open_output(&self) -> io::Result<Box<dyn Write + Send>> {
+        Ok(match self.output {
+            Some(ref path) => Box::new(File::create(path)?),
+            None => Box::new(io::stdout()),
+        })
+    }
+
+    fn monitors(&self) -> Vec<Box<dyn Monitor>> {
+        let mut monitors: Vec<Box<dyn Monitor>> = Vec::new();
+
+        if self.progress {
+            monitors.push(Box::new(Progress::new()));
+        }
+
+        if self.timing {
+            monitors.push(Box::new(Timings::new()));
+        }
+
+        if let Some(ref path) = self.timing_file {
+            monitors.push(Box::new(Timings::new().csv_file(path.clone())));
+        }
+
+        monitors
+    }
+
+    /// Run a solver with these options
+    pub fn execute<S>(&self, solver: S) -> Result<(), SolverError<S>>
+        where
+            S: Solver + Sync,
+            S::GlobalData: LoadGlobalData + Sync,
+            S::CaseData: Group + Send,
+            S::Solution: Display + Send,
+    {
+        let input = self.open_input().unwrap_or_else(|err| {
+            eprintln!("error opening input: {}", err);
+            exit(1);
+        });
+        let output = self.open_output().unwrap_or_else(|err| {
+            eprintln!("error opening output: {}", err);
+            exit(1);
+        });
+
+        let journal = self.journal.as_ref().map(|path| {
+            Journal::open(path).unwrap_or_else(|err| {
+                eprintln!("error opening journal {}: {}", path.display(), err);
+                exit(1);
+            })
+        });
+
+        set_debug(self.debug);
+
+        let tokens = TokensReader::new(input);
+        let template = match self.precision {
+            Some(precision) => self.template.clone().fixed(precision),
+            None => self.template.clone(),
+        };
+        let printer = TemplatePrinter::new(output, template);
+        let monitor = self.monitors();
+
+        match self.executor {
+            ExecutorChoice::Sequential => self
+                .configure(SequentialExecutor::new().monitor(monitor), journal)
+                .execute(tokens, printer, solver),
+            ExecutorChoice::Threads(threads) => {
+                let executor = match threads {
+                    Some(threads) => ThreadExecutor::with_threads(threads),
+                    None => ThreadExecutor::new(),
+                };
+                let executor = match self.reorder_window {
+                    Some(window) => executor.with_reorder_window(window),
+                    None => executor,
+                };
+
+                self.configure(executor.monitor(monitor), journal)
+                    .execute(tokens, printer, solver)
+            }
+            #[cfg(feature = "rayon")]
+            ExecutorChoice::Rayon => self
+                .configure(RayonExecutor::new().monitor(monitor), journal)
+                .execute(tokens, printer, solver),
+        }
+    }
+
+    // Apply the options which every executor shares
+    fn configure<E: ExecutorSettings>(&self, mut executor: E, journal: Option<Journal>) -> E {
+        let settings = executor.settings_mut();
+        settings.time_limit = self.time_limit;
+        settings.selection = self.cases.clone();
+        settings.trace = self.trace.clone();
+        settings.journal = journal.map(Arc::new);
+        executor
+    }
+}
+
+/// Run a solver as a complete program, configured by its command line
+/// arguments. This is intended to be the whole of a solution's `main`:
+///
+/// ```ignore
+/// fn main() {
+///     run_cli(solver(|data: Data| solve(data)));
+/// }
+/// ```
+pub fn run_cli<S>(solver: S)
+    where
+        S: Solver + Sync,
+        S::GlobalData: LoadGlobalData + Sync,
+        S::CaseData: Group + Send,
+        S::Solution: Display + Send,
+{
+    CliOptions::from_env().execute(solver).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::case_index::CaseIndex;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, CliError> {
+        CliOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn invalid_message(args: &[&str]) -> String {
+        match parse(args) {
+            Err(CliError::Invalid(message)) => message,
+            other => panic!("expected {:?} to be invalid, got {:?}", args, other),
+        }
+    }
+
+    #[test]
+    fn positional_paths_are_input_then_output() {
+        let options = parse(&["in.txt", "out.txt"]).unwrap();
+
+        assert_eq!(options.input, Some(PathBuf::from("in.txt")));
+        assert_eq!(options.output, Some(PathBuf::from("out.txt")));
+        assert_eq!(invalid_message(&["a", "b", "c"]), "unexpected argument: c");
+    }
+
+    #[test]
+    fn dash_means_stdin_or_stdout() {
+        let options = parse(&["-", "out.txt"]).unwrap();
+        assert_eq!(options.input, None);
+        assert_eq!(options.output, Some(PathBuf::from("out.txt")));
+
+        let options = parse(&["-i", "in.txt", "-o", "-"]).unwrap();
+        assert_eq!(options.input, Some(PathBuf::from("in.txt")));
+        assert_eq!(options.output, None);
+    }
+
+    #[test]
+    fn missing_values_are_invalid() {
+        assert_eq!(invalid_message(&["-i"]), "missing value for -i");
+        assert_eq!(invalid_message(&["--cases"]), "missing value for --cases");
+        assert_eq!(invalid_message(&["-p", "--time-limit"]), "missing value for --time-limit");
+    }
+
+    #[test]
+    fn unknown_flags_are_invalid() {
+        assert_eq!(invalid_message(&["--fast"]), "unknown option: --fast");
+        assert_eq!(invalid_message(&["-x", "in.txt"]), "unknown option: -x");
+        assert_eq!(invalid_message(&["-e", "gpu"]), "unknown executor: gpu");
+        assert_eq!(parse(&["--help"]).unwrap_err(), CliError::Help);
+    }
+
+    #[test]
+    fn thread_count_implies_the_thread_executor() {
+        assert_eq!(parse(&[]).unwrap().executor, ExecutorChoice::Threads(None));
+        assert_eq!(
+            parse(&["-e", "sequential", "-j", "3"]).unwrap().executor,
+            ExecutorChoice::Threads(Some(3)),
+        );
+        assert_eq!(invalid_message(&["-j", "many"]), "invalid thread count: many");
+    }
+
+    #[test]
+    fn flags_are_parsed() {
+        let options = parse(&["--no-debug", "-p", "--timing", "-c", "2-3", "--precision", "6"]).unwrap();
+
+        assert!(!options.debug);
+        assert!(options.progress);
+        assert!(options.timing);
+        assert_eq!(options.precision, Some(6));
+        assert!(options.cases.contains(CaseIndex::new(2).unwrap()));
+        assert!(!options.cases.contains(CaseIndex::new(4).unwrap()));
+    }
+
+    #[test]
+    fn time_limits_are_in_seconds() {
+        assert_eq!(parse_seconds("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_seconds("0.25"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_seconds("-1"), None);
+        assert_eq!(parse_seconds("NaN"), None);
+        assert_eq!(parse_seconds("inf"), None);
+        assert_eq!(parse_seconds("soon"), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::cell::Cell;
+use std::time::Instant;
+
+use crate::case_index::CaseIndex;
+
+/// What an executor knows about the case being solved on a thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaseContext {
+    pub case: CaseIndex,
+    /// When the case's time limit runs out, if it has one
+    pub deadline: Option<Instant>,
+    /// Whether `trace!` output is enabled for the case
+    pub traced: bool,
+}
+
+impl CaseContext {
+    pub fn new(case: CaseIndex) -> Self {
+        CaseContext {
+            case,
+            deadline: None,
+            traced: false,
+        }
+    }
+
+    pub fn deadline(self, deadline: Option<Instant>) -> Self {
+        CaseContext { deadline, ..self }
+    }
+
+    pub fn traced(self, traced: bool) -> Self {
+        CaseContext { traced, ..self }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<CaseContext>> = Cell::new(None);
+}
+
+/// Get the context of the case being solved on this thread, if any.
+/// Executors set this for the duration of each call to `Solver::solve_case`.
+pub fn current() -> Option<CaseContext> {
+    CURRENT.with(|current| current.get())
+}
+
+/// Get the case being solved on this thread, if any
+pub fn current_case() -> Option<CaseIndex> {
+    current().map(|context| context.case)
+}
+
+#[derive(Debug)]
+pub struct CaseGuard {
+    previous: Option<CaseContext>,
+}
+
+impl Drop for CaseGuard {
+    fn drop(&mut self) {
+        let previous = self.previous;
+        CURRENT.with(move |current| current.set(previous));
+    }
+}
+
+/// Set the context of this thread until the returned guard is dropped
+pub fn enter(context: CaseContext) -> CaseGuard {
+    CaseGuard {
+        previous: CURRENT.with(move |current| current.replace(Some(context))),
+    }
+}
+
+/// Mark this thread as solving `case` until the returned guard is dropped
+pub fn enter_case(case: CaseIndex) -> CaseGuard {
+    enter(CaseContext::new(case))
+}
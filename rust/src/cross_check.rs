@@ -0,0 +1,130 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+use crate::case_index::CaseIndex;
+use crate::context;
+use crate::solver::Solver;
+
+/// A case where a solver and its reference solver disagreed
+#[derive(Debug, Clone)]
+pub struct Mismatch<CD, A, B> {
+    pub case: Option<CaseIndex>,
+    pub input: CD,
+    pub solution: A,
+    pub reference: B,
+}
+
+impl<CD: Debug, A: Debug, B: Debug> Display for Mismatch<CD, A, B> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.case {
+            Some(case) => writeln!(f, "{}: solutions differ", case)?,
+            None => writeln!(f, "solutions differ")?,
+        }
+        writeln!(f, "input:     {:?}", self.input)?;
+        writeln!(f, "solution:  {:?}", self.solution)?;
+        write!(f, "reference: {:?}", self.reference)
+    }
+}
+
+/// The mismatch between a solver and its reference solver
+pub type SolverMismatch<S, R> = Mismatch<<S as Solver>::CaseData, <S as Solver>::Solution, <R as Solver>::Solution>;
+
+/// Solve a case with both solvers, returning the solution from `solver` if
+/// `compare` accepts it, or the details of the mismatch if not.
+pub fn compare_solutions<S, R, C>(
+    solver: &S,
+    reference: &R,
+    compare: C,
+    global_data: &S::GlobalData,
+    case_data: S::CaseData,
+) -> Result<S::Solution, SolverMismatch<S, R>>
+    where
+        S: Solver,
+        R: Solver<GlobalData = S::GlobalData, CaseData = S::CaseData>,
+        S::CaseData: Clone,
+        C: FnOnce(&S::Solution, &R::Solution) -> bool,
+{
+    let input = case_data.clone();
+    let reference_solution = reference.solve_case(global_data, case_data.clone());
+    let solution = solver.solve_case(global_data, case_data);
+
+    if compare(&solution, &reference_solution) {
+        Ok(solution)
+    } else {
+        Err(Mismatch {
+            case: context::current_case(),
+            input,
+            solution,
+            reference: reference_solution,
+        })
+    }
+}
+
+pub fn solutions_equal<A: PartialEq<B>, B>(solution: &A, reference: &B) -> bool {
+    solution == reference
+}
+
+/// A solver which runs a second, reference solver on every case, and panics
+/// with the case, its input, and both solutions if they disagree.
+#[derive(Debug)]
+pub struct CrossCheck<S, R, C> {
+    solver: S,
+    reference: R,
+    compare: C,
+}
+
+impl<S, R, C> Solver for CrossCheck<S, R, C>
+    where
+        S: Solver,
+        R: Solver<GlobalData = S::GlobalData, CaseData = S::CaseData>,
+        S::CaseData: Clone + Debug,
+        S::Solution: Debug,
+        R::Solution: Debug,
+        C: Fn(&S::Solution, &R::Solution) -> bool,
+{
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = S::Solution;
+
+    fn solve_case(
+        &self,
+        global_data: &Self::GlobalData,
+        case_data: Self::CaseData,
+    ) -> Self::Solution {
+        compare_solutions(
+            &self.solver,
+            &self.reference,
+            &self.compare,
+            global_data,
+            case_data,
+        ).unwrap_or_else(|mismatch| panic!("{}", mismatch))
+    }
+}
+
+pub type SolutionsEqual<A, B> = fn(&A, &B) -> bool;
+
+pub trait CrossCheckSolver: Solver + Sized {
+    /// Check every solution against a reference solver with `==`
+    fn cross_check<R>(self, reference: R) -> CrossCheck<Self, R, SolutionsEqual<Self::Solution, R::Solution>>
+        where
+            R: Solver<GlobalData = Self::GlobalData, CaseData = Self::CaseData>,
+            Self::Solution: PartialEq<R::Solution>,
+    {
+        self.cross_check_by(reference, solutions_equal)
+    }
+
+    /// Check every solution against a reference solver with a custom
+    /// comparison, for problems with more than one correct answer.
+    fn cross_check_by<R, C>(self, reference: R, compare: C) -> CrossCheck<Self, R, C>
+        where
+            R: Solver<GlobalData = Self::GlobalData, CaseData = Self::CaseData>,
+            C: Fn(&Self::Solution, &R::Solution) -> bool,
+    {
+        CrossCheck {
+            solver: self,
+            reference,
+            compare,
+        }
+    }
+}
+
+impl<S: Solver> CrossCheckSolver for S {}
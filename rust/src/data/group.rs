@@ -164,7 +164,7 @@ macro_rules! tuple_group {
                 let ($($tail,)*) = tokens.next()?;
                 let last = tokens.next().map_err(|err| TupleGroupError::new(count!($($tail),*), err))?;
 
-                Ok(($($tail,)*  last))
+                Ok(($($tail,)* last,))
             }
         }
     }
@@ -213,13 +213,49 @@ macro_rules! load_field {
     };
 }
 
+/// Declare structs which load their fields in order, as a `Group`. A field
+/// followed by `=> size` is a collection of that many items, where `size` may
+/// refer to earlier fields. Structs marked `#[write_tokens]` also implement
+/// `WriteTokens`, which requires every field type to implement it.
 #[macro_export]
 macro_rules! struct_groups {
-    ($(
+    () => ();
+    (
+        #[write_tokens]
+        $(#[derive($($derive:ident),+)])*
+        struct $Name:ident {
+            $($field:ident : $type:ty $(=> $size:expr )* ,)*
+        }
+        $($rest:tt)*
+    ) => (
+        struct_groups!{
+            $(#[derive($($derive),+)])*
+            struct $Name {
+                $($field : $type $(=> $size)* ,)*
+            }
+        }
+
+        impl $crate::data::WriteTokens for $Name {
+            fn write_tokens<W: std::fmt::Write>(
+                &self,
+                out: &mut $crate::data::TokenWriter<W>,
+            ) -> std::fmt::Result {
+                $(
+                    $crate::data::WriteTokens::write_tokens(&self.$field, out)?;
+                )*
+                Ok(())
+            }
+        }
+
+        struct_groups!{$($rest)*}
+    );
+    (
         $(#[derive($($derive:ident),+)])*
         struct $Name:ident {
-        $($field:ident : $type:ty $(=> $size:expr )* ,)*
-    })+) => ($(
+            $($field:ident : $type:ty $(=> $size:expr )* ,)*
+        }
+        $($rest:tt)*
+    ) => (
         #[derive(Debug, $($($derive,)*)*)]
         pub struct $Name {
             $(pub $field: $type,)*
@@ -239,5 +275,7 @@ macro_rules! struct_groups {
                 )*})
             }
         }
-    )+)
+
+        struct_groups!{$($rest)*}
+    );
 }
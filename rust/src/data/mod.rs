@@ -1,6 +1,8 @@
 mod global;
 
 pub mod group;
+pub mod write;
 
 pub use self::global::{CountPrefix, CountSuffix, GlobalData, GlobalDataError, LoadGlobalData, NoGlobalData};
 pub use self::group::{Group, StructGroupError, TupleGroupError, TokenError, UsizeTokenError, ViaFromStr};
+pub use self::write::{input_text, write_input, AsTokens, Grid, LengthPrefixed, Lines, TokenWriter, WriteGlobalData, WriteTokens};
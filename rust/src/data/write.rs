@@ -0,0 +1,397 @@
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::str::FromStr;
+
+use num_traits::Float;
+use ordered_float::{NotNan, OrderedFloat};
+
+use crate::data::{CountPrefix, CountSuffix, NoGlobalData, ViaFromStr};
+
+/// Writes whitespace-separated tokens, tracking line breaks so that values
+/// can be laid out the way they'd appear in a Code Jam input file.
+#[derive(Debug)]
+pub struct TokenWriter<W: fmt::Write> {
+    writer: W,
+    line_start: bool,
+}
+
+impl<W: fmt::Write> TokenWriter<W> {
+    pub fn new(writer: W) -> Self {
+        TokenWriter {
+            writer,
+            line_start: true,
+        }
+    }
+
+    /// Write a single token, separated by a space from any previous token
+    /// on the same line.
+    pub fn token(&mut self, token: impl Display) -> fmt::Result {
+        if !self.line_start {
+            self.writer.write_char(' ')?;
+        }
+        self.line_start = false;
+        write!(self.writer, "{}", token)
+    }
+
+    /// End the current line, if anything has been written to it
+    pub fn end_line(&mut self) -> fmt::Result {
+        if !self.line_start {
+            self.line_start = true;
+            self.writer.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    pub fn write<T: WriteTokens + ?Sized>(&mut self, value: &T) -> fmt::Result {
+        value.write_tokens(self)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// The inverse of `Group`: a value which can write itself as tokens, such
+/// that `Group::from_tokens` would read it back.
+pub trait WriteTokens {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result;
+}
+
+impl<T: WriteTokens + ?Sized> WriteTokens for &T {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        (*self).write_tokens(out)
+    }
+}
+
+#[macro_export]
+macro_rules! token_via_display {
+    ( $( $type:ty )+ ) => {$(
+        impl $crate::data::WriteTokens for $type {
+            fn write_tokens<W: std::fmt::Write>(&self, out: &mut $crate::data::TokenWriter<W>) -> std::fmt::Result {
+                out.token(self)
+            }
+        }
+    )*}
+}
+
+token_via_display!{
+    i8 i16 i32 i64 i128 isize
+    u8 u16 u32 u64 u128 usize
+    f32 f64
+    char String str
+}
+
+impl<T: FromStr + Display> WriteTokens for ViaFromStr<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        out.token(&self.0)
+    }
+}
+
+impl<T: WriteTokens + Float> WriteTokens for OrderedFloat<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        self.0.write_tokens(out)
+    }
+}
+
+impl<T: WriteTokens + Float> WriteTokens for NotNan<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        self.into_inner().write_tokens(out)
+    }
+}
+
+impl WriteTokens for () {
+    fn write_tokens<W: fmt::Write>(&self, _out: &mut TokenWriter<W>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+macro_rules! tuple_write_tokens {
+    () => ();
+    ($field:ident $(, $tail:ident)*) => {
+        tuple_write_tokens!{$($tail),*}
+
+        #[allow(non_snake_case)]
+        impl< $field : WriteTokens $(, $tail : WriteTokens)* > WriteTokens for ($field, $($tail,)*) {
+            fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+                let ($field, $($tail,)*) = self;
+                $field.write_tokens(out)?;
+                $( $tail.write_tokens(out)?; )*
+                Ok(())
+            }
+        }
+    }
+}
+
+tuple_write_tokens!{A, B, C, D, E, F, G, H, I, J, K, L}
+
+/// Slices and vectors are written on a single line, without a length
+impl<T: WriteTokens> WriteTokens for [T] {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        self.iter().try_for_each(|item| item.write_tokens(out))
+    }
+}
+
+impl<T: WriteTokens> WriteTokens for Vec<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        self.as_slice().write_tokens(out)
+    }
+}
+
+/// A list written on a single line, preceded by its length
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LengthPrefixed<T>(pub Vec<T>);
+
+impl<T: WriteTokens> WriteTokens for LengthPrefixed<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        out.token(self.0.len())?;
+        self.0.write_tokens(out)
+    }
+}
+
+/// A list with each item written on its own line
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Lines<T>(pub Vec<T>);
+
+impl<T: WriteTokens> WriteTokens for Lines<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        out.end_line()?;
+        self.0.iter().try_for_each(|item| {
+            item.write_tokens(out)?;
+            out.end_line()
+        })
+    }
+}
+
+/// A grid, with each row written on its own line
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Grid<T>(pub Vec<Vec<T>>);
+
+impl<T: WriteTokens> WriteTokens for Grid<T> {
+    fn write_tokens<W: fmt::Write>(&self, out: &mut TokenWriter<W>) -> fmt::Result {
+        out.end_line()?;
+        self.0.iter().try_for_each(|row| {
+            row.write_tokens(out)?;
+            out.end_line()
+        })
+    }
+}
+
+/// Adapter to `Display` a value as tokens, as it would appear in an input file
+#[derive(Debug, Clone, Copy)]
+pub struct AsTokens<T>(pub T);
+
+impl<T: WriteTokens> Display for AsTokens<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        TokenWriter::new(f).write(&self.0)
+    }
+}
+
+/// The inverse of `LoadGlobalData`: writes the case count and global data
+pub trait WriteGlobalData {
+    fn write_global<W: fmt::Write>(&self, num_cases: usize, out: &mut TokenWriter<W>) -> fmt::Result;
+}
+
+impl WriteGlobalData for () {
+    fn write_global<W: fmt::Write>(&self, num_cases: usize, out: &mut TokenWriter<W>) -> fmt::Result {
+        out.token(num_cases)?;
+        out.end_line()
+    }
+}
+
+impl WriteGlobalData for NoGlobalData {
+    fn write_global<W: fmt::Write>(&self, num_cases: usize, out: &mut TokenWriter<W>) -> fmt::Result {
+        ().write_global(num_cases, out)
+    }
+}
+
+impl<T: WriteTokens> WriteGlobalData for CountPrefix<T> {
+    fn write_global<W: fmt::Write>(&self, num_cases: usize, out: &mut TokenWriter<W>) -> fmt::Result {
+        out.token(num_cases)?;
+        out.end_line()?;
+        self.0.write_tokens(out)?;
+        out.end_line()
+    }
+}
+
+impl<T: WriteTokens> WriteGlobalData for CountSuffix<T> {
+    fn write_global<W: fmt::Write>(&self, num_cases: usize, out: &mut TokenWriter<W>) -> fmt::Result {
+        self.0.write_tokens(out)?;
+        out.end_line()?;
+        out.token(num_cases)?;
+        out.end_line()
+    }
+}
+
+/// Write a complete input file: the case count and global data, followed by
+/// each case on its own line(s).
+pub fn input_text<G: WriteGlobalData, C: WriteTokens>(global_data: &G, cases: &[C]) -> String {
+    let mut out = TokenWriter::new(String::new());
+
+    global_data
+        .write_global(cases.len(), &mut out)
+        .and_then(|()| {
+            cases.iter().try_for_each(|case| {
+                case.write_tokens(&mut out)?;
+                out.end_line()
+            })
+        })
+        .expect("writing to a String can't fail");
+
+    out.into_inner()
+}
+
+pub fn write_input<G: WriteGlobalData, C: WriteTokens>(
+    mut writer: impl io::Write,
+    global_data: &G,
+    cases: &[C],
+) -> io::Result<()> {
+    writer.write_all(input_text(global_data, cases).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64;
+    use std::fmt::Debug;
+
+    use super::*;
+    use crate::data::{Group, LoadGlobalData, TokenError};
+    use crate::tokens::{Tokens, TokensReader};
+    use crate::{load_field, struct_groups, token_via_fromstr};
+
+    // A token type which can be read but not written
+    #[derive(Debug, PartialEq)]
+    pub struct Direction(char);
+
+    impl FromStr for Direction {
+        type Err = std::char::ParseCharError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            s.parse().map(Direction)
+        }
+    }
+
+    token_via_fromstr!{Direction}
+
+    struct_groups!{
+        #[write_tokens]
+        #[derive(Clone, PartialEq)]
+        struct Case {
+            n: usize,
+            values: Vec<i64> => n,
+            name: String,
+            f: f64,
+        }
+    }
+
+    // Structs without #[write_tokens] only need their fields to be groups
+    struct_groups!{
+        struct Move {
+            direction: Direction,
+            steps: u32,
+        }
+    }
+
+    fn round_trip<T: Group + WriteTokens + PartialEq + Debug>(value: T) {
+        let text = AsTokens(&value).to_string();
+        let back: T = TokensReader::new(text.as_bytes())
+            .next()
+            .unwrap_or_else(|err| panic!("failed to read back {:?}: {}", text, err));
+
+        assert_eq!(back, value, "{:?}", text);
+    }
+
+    fn read_all<T: Group>(text: &str, count: usize) -> Vec<T> {
+        TokensReader::new(text.as_bytes())
+            .collect(count)
+            .unwrap_or_else(|err| panic!("failed to read back {:?}: {}", text, err))
+    }
+
+    #[test]
+    fn scalars() {
+        round_trip(5i8);
+        round_trip(-12_345_678_901_234_567_890i128);
+        round_trip(u64::MAX);
+        round_trip('x');
+        round_trip("hello".to_string());
+        round_trip(ViaFromStr(std::net::Ipv4Addr::new(1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn floats() {
+        round_trip(0.1f64 + 0.2);
+        round_trip(1e-300f64);
+        round_trip(-1.5e300f64);
+        round_trip(f64::INFINITY);
+        round_trip(3.25f32);
+        round_trip(OrderedFloat(2.5f64));
+        round_trip(OrderedFloat(f64::NAN));
+        round_trip(NotNan::new(1e-7f64).unwrap());
+    }
+
+    #[test]
+    fn tuples() {
+        round_trip(());
+        round_trip((7u32,));
+        round_trip((1u8, 'c', "s".to_string(), 2.5f64));
+        round_trip((1, (2, 3), 4));
+    }
+
+    #[test]
+    fn lists() {
+        let values = vec![3, -1, 4];
+
+        let text = AsTokens(&values).to_string();
+        assert_eq!(text, "3 -1 4");
+        assert_eq!(read_all::<i32>(&text, 3), values);
+
+        let text = AsTokens(LengthPrefixed(values.clone())).to_string();
+        assert_eq!(text, "3 3 -1 4");
+        let mut tokens = TokensReader::new(text.as_bytes());
+        let len: usize = tokens.next().unwrap();
+        assert_eq!(tokens.collect::<i32, Vec<_>>(len).unwrap(), values);
+
+        let text = AsTokens(Lines(values.clone())).to_string();
+        assert_eq!(text, "3\n-1\n4\n");
+        assert_eq!(read_all::<i32>(&text, 3), values);
+    }
+
+    #[test]
+    fn grid() {
+        let rows = vec![vec!['a', 'b'], vec!['c', 'd'], vec!['e', 'f']];
+
+        let text = AsTokens(Grid(rows.clone())).to_string();
+        assert_eq!(text, "a b\nc d\ne f\n");
+
+        let mut tokens = TokensReader::new(text.as_bytes());
+        let back: Vec<Vec<char>> = (0..3).map(|_| tokens.collect(2).unwrap()).collect();
+        assert_eq!(back, rows);
+    }
+
+    #[test]
+    fn struct_with_sized_field() {
+        let case = Case {
+            n: 3,
+            values: vec![1, -2, 3],
+            name: "abc".into(),
+            f: 0.5,
+        };
+        round_trip(case.clone());
+
+        let text = input_text(&CountSuffix(7u32), &[case.clone(), case.clone()]);
+        let mut tokens = TokensReader::new(text.as_bytes());
+        let global_data = CountSuffix::<u32>::from_tokens(&mut tokens).unwrap();
+
+        assert_eq!(global_data.num_cases, 2);
+        assert_eq!(global_data.data.0, 7);
+        assert_eq!(tokens.next::<Case>().unwrap(), case);
+        assert_eq!(tokens.next::<Case>().unwrap(), case);
+    }
+
+    #[test]
+    fn struct_without_write_tokens() {
+        let step: Move = TokensReader::new("N 3".as_bytes()).next().unwrap();
+
+        assert_eq!(step.direction, Direction('N'));
+        assert_eq!(step.steps, 3);
+    }
+}
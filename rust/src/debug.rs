@@ -0,0 +1,53 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::context;
+
+static DEBUG_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn `debug!` and `trace!` output on or off for the whole program
+pub fn set_debug(enabled: bool) {
+    DEBUG_ENABLED.store(enabled, Ordering::Relaxed)
+}
+
+pub fn debug_enabled() -> bool {
+    DEBUG_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Whether the case being solved on this thread was chosen for verbose
+/// output with an executor's `trace` option. Use this to guard expensive
+/// debugging code, or to break into a specific case.
+pub fn traced() -> bool {
+    debug_enabled() && context::current().map_or(false, |context| context.traced)
+}
+
+/// Write a line to stderr, prefixed with the current case, if any. This is
+/// the implementation of `debug!` and `trace!`.
+pub fn write_debug(args: fmt::Arguments) {
+    match context::current_case() {
+        Some(case) => eprintln!("[{}] {}", case, args),
+        None => eprintln!("{}", args),
+    }
+}
+
+/// Print a line to stderr, prefixed with the case being solved. This can be
+/// turned off at runtime with `set_debug(false)`.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::debug::debug_enabled() {
+            $crate::debug::write_debug(format_args!($($arg)*))
+        }
+    }
+}
+
+/// Like `debug!`, but only prints in the cases chosen with an executor's
+/// `trace` option.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::debug::traced() {
+            $crate::debug::write_debug(format_args!($($arg)*))
+        }
+    }
+}
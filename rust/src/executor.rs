@@ -1,23 +1,35 @@
+use std::any::Any;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 use std::io;
+use std::iter;
+use std::panic::{self, AssertUnwindSafe};
 use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use derive_more::From;
 
 use crossbeam::{self, channel};
 
-use crate::case_index::CaseIndex;
+use crate::case_index::{case_range, CaseIndex};
+use crate::context::{self, CaseContext};
+use crate::journal::Journal;
+use crate::monitor::Monitor;
 use crate::printer::Printer;
+use crate::selection::CaseSelection;
 use crate::solver::Solver;
 use crate::tokens::Tokens;
-use crate::data::{GlobalDataError, LoadGlobalData, Group};
+use crate::data::{GlobalData, GlobalDataError, LoadGlobalData, Group};
 
 #[derive(Debug)]
 pub enum CaseErrorKind<E: Error> {
     Load(E),
     Print(io::Error),
+    Journal(io::Error),
 }
 
 #[derive(Debug)]
@@ -41,6 +53,11 @@ impl<E: Error> CaseError<E> {
     pub fn print_error(case: CaseIndex, err: io::Error) -> Self {
         CaseError::new(case, CaseErrorKind::Print(err))
     }
+
+    #[inline(always)]
+    pub fn journal_error(case: CaseIndex, err: io::Error) -> Self {
+        CaseError::new(case, CaseErrorKind::Journal(err))
+    }
 }
 
 impl<E: Error> Display for CaseError<E> {
@@ -52,6 +69,9 @@ impl<E: Error> Display for CaseError<E> {
             CaseErrorKind::Print(ref err) => {
                 write!(f, "error writing solution to {}: {}", self.case, err)
             }
+            CaseErrorKind::Journal(ref err) => {
+                write!(f, "error recording solution to {} in the journal: {}", self.case, err)
+            }
         }
     }
 }
@@ -61,6 +81,7 @@ impl<E: Error> Error for CaseError<E> {
         match self.error {
             CaseErrorKind::Load(ref err) => Some(err),
             CaseErrorKind::Print(ref err) => Some(err),
+            CaseErrorKind::Journal(ref err) => Some(err),
         }
     }
 }
@@ -106,113 +127,667 @@ impl<E1: Error, E2: Error> Error for ExecutionError<E1, E2> {
     }
 }
 
-type SolverError<S> = ExecutionError<<<S as Solver>::GlobalData as LoadGlobalData>::Err, <<S as Solver>::CaseData as Group>::Err>;
+pub type SolverError<S> = ExecutionError<<<S as Solver>::GlobalData as LoadGlobalData>::Err, <<S as Solver>::CaseData as Group>::Err>;
+
+/// Solve a single case, with the case context set for the solver as an
+/// executor would set it. Use this to call a solver directly, for instance in
+/// a unit test, if it relies on the context, like a `with_case_index` solver.
+pub fn solve_case<S: Solver>(
+    solver: &S,
+    case: CaseIndex,
+    global_data: &S::GlobalData,
+    case_data: S::CaseData,
+) -> S::Solution {
+    solve_in_context(solver, CaseContext::new(case), global_data, case_data)
+}
+
+/// Solve a single case with a complete case context, including its deadline
+/// and whether it's traced. Every executor solves cases through this.
+pub fn solve_in_context<S: Solver>(
+    solver: &S,
+    context: CaseContext,
+    global_data: &S::GlobalData,
+    case_data: S::CaseData,
+) -> S::Solution {
+    let _context = context::enter(context);
+    solver.solve_case(global_data, case_data)
+}
 
-pub trait Executor<T: Tokens, P: Printer, S: Solver>
+/// The settings shared by every executor, which are set through
+/// `ExecutorSettings`
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub(crate) time_limit: Option<Duration>,
+    pub(crate) selection: CaseSelection,
+    pub(crate) trace: Option<CaseSelection>,
+    pub(crate) journal: Option<Arc<Journal>>,
+}
+
+/// Builder methods for the settings shared by every executor
+pub trait ExecutorSettings: Sized {
+    #[doc(hidden)]
+    fn settings_mut(&mut self) -> &mut Settings;
+
+    /// Give each case a time limit. Cases which overrun it get a warning on
+    /// stderr and are reported to the monitor, and solvers can check for it
+    /// with a `Cancellation`.
+    fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.settings_mut().time_limit = Some(time_limit);
+        self
+    }
+
+    /// Only solve and print the selected cases
+    fn cases(mut self, selection: CaseSelection) -> Self {
+        self.settings_mut().selection = selection;
+        self
+    }
+
+    /// Enable `trace!` output for the selected cases
+    fn trace(mut self, trace: CaseSelection) -> Self {
+        self.settings_mut().trace = Some(trace);
+        self
+    }
+
+    /// Record each solution in a journal as it's printed. Cases which are
+    /// already in the journal aren't solved again; their recorded solutions
+    /// are printed in their place.
+    fn journal(mut self, journal: Journal) -> Self {
+        self.settings_mut().journal = Some(Arc::new(journal));
+        self
+    }
+}
+
+// Ways printing a solution can fail
+pub(crate) enum OutputError {
+    Print(io::Error),
+    Journal(io::Error),
+}
+
+impl OutputError {
+    pub(crate) fn into_case_error<E: Error>(self, case: CaseIndex) -> CaseError<E> {
+        match self {
+            OutputError::Print(err) => CaseError::print_error(case, err),
+            OutputError::Journal(err) => CaseError::journal_error(case, err),
+        }
+    }
+}
+
+impl Settings {
+    // The solution to a case from a previous run, if there is a journal
+    pub(crate) fn replay(&self, case: CaseIndex) -> Option<&str> {
+        self.journal.as_ref().and_then(|journal| journal.get(case))
+    }
+
+    // Whether a case needs to be solved in this run
+    pub(crate) fn pending(&self, case: CaseIndex) -> bool {
+        self.selection.contains(case) && self.replay(case).is_none()
+    }
+
+    // How many of the first `num_cases` cases need to be solved in this run
+    pub(crate) fn count_pending(&self, num_cases: usize) -> usize {
+        case_range(num_cases).filter(|&case| self.pending(case)).count()
+    }
+
+    // Print a solution, then record it in the journal, if there is one
+    pub(crate) fn print<P: Printer, T: Display>(&self, printer: &mut P, case: CaseIndex, solution: T) -> Result<(), OutputError> {
+        match self.journal {
+            None => printer.print_solution(case, solution).map_err(OutputError::Print),
+            Some(ref journal) => {
+                let solution = solution.to_string();
+
+                printer.print_solution(case, &solution).map_err(OutputError::Print)?;
+                journal.record(case, &solution).map_err(OutputError::Journal)
+            }
+        }
+    }
+
+    // Pair the data for each selected case with its index. `cases` is
+    // advanced once for every case, selected or not, and load errors are
+    // passed through even for unselected cases, since the rest of the input
+    // can't be read after one. Load times are reported to the monitor for
+    // every case except those replayed from the journal.
+    pub(crate) fn load_cases<'a, GD, CD, E, I, M>(
+        &'a self,
+        global_data: &'a GlobalData<GD>,
+        cases: I,
+        monitor: &'a M,
+    ) -> impl Iterator<Item = (CaseIndex, Result<CD, E>)> + 'a
+        where
+            I: Iterator<Item = Result<CD, E>> + 'a,
+            M: Monitor,
+    {
+        global_data
+            .cases()
+            .scan(cases, move |cases, (case, _)| {
+                let start = Instant::now();
+                let case_data = cases.next()?;
+                Some((case, case_data, start.elapsed()))
+            })
+            .filter(move |(case, case_data, _)| case_data.is_err() || self.selection.contains(*case))
+            .map(move |(case, case_data, load_time)| {
+                if case_data.is_ok() && self.replay(case).is_none() {
+                    monitor.case_loaded(case, load_time);
+                }
+                (case, case_data)
+            })
+    }
+
+    // Solve a case with its context set, reporting the solve time to the
+    // monitor, and warning about and reporting any overrun of the time limit.
+    pub(crate) fn solve<S: Solver, M: Monitor>(
+        &self,
+        solver: &S,
+        case: CaseIndex,
+        global_data: &S::GlobalData,
+        case_data: S::CaseData,
+        monitor: &M,
+    ) -> S::Solution {
+        let start = Instant::now();
+        let context = CaseContext::new(case)
+            .deadline(self.time_limit.map(|limit| start + limit))
+            .traced(self.trace.as_ref().map_or(false, |trace| trace.contains(case)));
+
+        let solution = solve_in_context(solver, context, global_data, case_data);
+
+        let solve_time = start.elapsed();
+        monitor.case_solved(case, solve_time);
+
+        if let Some(limit) = self.time_limit.filter(|&limit| solve_time > limit) {
+            eprintln!(
+                "warning: {} took {:?}, over its time limit of {:?}",
+                case, solve_time, limit,
+            );
+            monitor.case_timed_out(case, solve_time);
+        }
+
+        solution
+    }
+}
+
+pub trait Executor<P: Printer, S: Solver>
     where
-        S::GlobalData: LoadGlobalData,
-        S::CaseData: Group,
         S::Solution: Display,
 {
-    fn execute(tokens: T, printer: P, solver: S) -> Result<(), SolverError<S>>;
+    /// Solve and print every case in a problem. `cases` yields the data for
+    /// each case in order; it is only advanced `global_data.num_cases` times.
+    fn execute_cases<E: Error, I: Iterator<Item = Result<S::CaseData, E>>>(
+        &self,
+        global_data: &GlobalData<S::GlobalData>,
+        cases: I,
+        printer: P,
+        solver: S,
+    ) -> Result<(), CaseError<E>>;
+
+    fn execute<T: Tokens>(&self, mut tokens: T, printer: P, solver: S) -> Result<(), SolverError<S>>
+        where
+            S::GlobalData: LoadGlobalData,
+            S::CaseData: Group,
+    {
+        let global_data = tokens.start_problem()?;
+        let cases = iter::repeat_with(move || tokens.next());
+
+        self.execute_cases(&global_data, cases, printer, solver)
+            .map_err(ExecutionError::Case)
+    }
 
-    fn run(tokens: T, printer: P, solver: S) {
-        Self::execute(tokens, printer, solver).unwrap_or_else(|err| {
+    fn run<T: Tokens>(&self, tokens: T, printer: P, solver: S)
+        where
+            S::GlobalData: LoadGlobalData,
+            S::CaseData: Group,
+    {
+        self.execute(tokens, printer, solver).unwrap_or_else(|err| {
             eprintln!("{}", err);
             exit(1);
         })
     }
 }
 
-pub struct SequentialExecutor;
+/// An executor which loads, solves and prints each case in turn on the
+/// current thread. Build one with `SequentialExecutor::new()`, then add a
+/// monitor and settings before running it.
+#[derive(Debug, Clone, Default)]
+pub struct SequentialExecutor<M = ()> {
+    monitor: M,
+    settings: Settings,
+}
+
+impl SequentialExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M> SequentialExecutor<M> {
+    /// Report the progress of each run to a monitor
+    pub fn monitor<M2: Monitor>(self, monitor: M2) -> SequentialExecutor<M2> {
+        SequentialExecutor {
+            monitor,
+            settings: self.settings,
+        }
+    }
+}
 
-impl<T: Tokens, P: Printer, S: Solver> Executor<T, P, S> for SequentialExecutor
+impl<M> ExecutorSettings for SequentialExecutor<M> {
+    fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+}
+
+impl<P: Printer, S: Solver, M: Monitor> Executor<P, S> for SequentialExecutor<M>
     where
-        S::GlobalData: LoadGlobalData,
-        S::CaseData: Group,
         S::Solution: Display,
 {
-    fn execute(mut tokens: T, mut printer: P, solver: S) -> Result<(), SolverError<S>> {
-        tokens
-            .start_problem()?
-            .cases()
-            .try_for_each(move |(case, global_data)| {
-                let case_data = tokens
-                    .next()
-                    .map_err(|err| ExecutionError::load_error(case, err))?;
-                let solution = solver.solve_case(global_data, case_data);
-                printer
+    fn execute_cases<E: Error, I: Iterator<Item = Result<S::CaseData, E>>>(
+        &self,
+        global_data: &GlobalData<S::GlobalData>,
+        cases: I,
+        mut printer: P,
+        solver: S,
+    ) -> Result<(), CaseError<E>> {
+        let monitor = &self.monitor;
+        let settings = &self.settings;
+        monitor.start(settings.count_pending(global_data.num_cases));
+
+        let result = settings.load_cases(global_data, cases, monitor).try_for_each(|(case, case_data)| {
+            let case_data = case_data.map_err(|err| CaseError::load_error(case, err))?;
+
+            if let Some(solution) = settings.replay(case) {
+                return printer
                     .print_solution(case, solution)
-                    .map_err(|err| ExecutionError::print_error(case, err))
-            })
+                    .map_err(|err| CaseError::print_error(case, err));
+            }
+
+            let solution = settings.solve(&solver, case, &global_data.data, case_data, monitor);
+
+            settings
+                .print(&mut printer, case, solution)
+                .map_err(|err| err.into_case_error(case))?;
+            monitor.case_printed(case);
+            Ok(())
+        });
+
+        monitor.finish();
+        result
     }
 }
 
-pub struct ThreadExecutor;
+// Reasons the print thread in ThreadExecutor can stop early
+enum PrintFailure {
+    Output(CaseIndex, OutputError),
+    Panic(Box<dyn Any + Send>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreadExecutor<M = ()> {
+    num_threads: usize,
+    window: usize,
+    monitor: M,
+    settings: Settings,
+}
 
-impl<T: Tokens + Send, P: Printer + Send, S: Solver + Sync> Executor<T, P, S> for ThreadExecutor
+impl ThreadExecutor {
+    /// Create an executor with one worker thread per CPU
+    pub fn new() -> Self {
+        Self::with_threads(num_cpus::get())
+    }
+
+    pub fn with_threads(num_threads: usize) -> Self {
+        let num_threads = num_threads.max(1);
+
+        ThreadExecutor {
+            num_threads,
+            window: num_threads * 4,
+            monitor: (),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl<M> ThreadExecutor<M> {
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    pub fn reorder_window(&self) -> usize {
+        self.window
+    }
+
+    /// Set the most cases which may be in progress at once, counting from
+    /// the earliest case which hasn't been printed yet. When a slow case holds
+    /// up printing, loading stalls once this many later cases are solved or
+    /// being solved, so memory use is bounded by the window rather than by
+    /// the number of cases. The default is 4 cases per thread.
+    pub fn with_reorder_window(self, window: usize) -> Self {
+        ThreadExecutor {
+            window: window.max(1),
+            ..self
+        }
+    }
+
+    /// Report the progress of each run to a monitor
+    pub fn monitor<M2: Monitor>(self, monitor: M2) -> ThreadExecutor<M2> {
+        ThreadExecutor {
+            num_threads: self.num_threads,
+            window: self.window,
+            monitor,
+            settings: self.settings,
+        }
+    }
+}
+
+impl Default for ThreadExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> ExecutorSettings for ThreadExecutor<M> {
+    fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+}
+
+impl<P: Printer + Send, S: Solver + Sync, M: Monitor> Executor<P, S> for ThreadExecutor<M>
     where
-        S::GlobalData: LoadGlobalData + Sync,
-        S::CaseData: Group + Send,
+        S::GlobalData: Sync,
+        S::CaseData: Send,
         S::Solution: Display + Send,
-        SolverError<S>: Send,
 {
-    fn execute(mut tokens: T, mut printer: P, solver: S, ) -> Result<(), SolverError<S>> {
-        let global_data = &tokens.start_problem()?;
+    fn execute_cases<E: Error, I: Iterator<Item = Result<S::CaseData, E>>>(
+        &self,
+        global_data: &GlobalData<S::GlobalData>,
+        cases: I,
+        mut printer: P,
+        solver: S,
+    ) -> Result<(), CaseError<E>> {
         let solver = &solver;
+        let panicked = &AtomicBool::new(false);
+        let monitor = &self.monitor;
+        let settings = &self.settings;
 
-        crossbeam::scope(move |scope| {
-            let (sender, receiver) = channel::bounded(global_data.num_cases);
+        monitor.start(settings.count_pending(global_data.num_cases));
+
+        let result = crossbeam::scope(move |scope| {
+            let (case_sender, case_receiver) = channel::bounded(self.num_threads);
+
+            // Every case holds a permit from when it's loaded until it's
+            // printed, so at most `window` solutions are ever pending.
+            let (permit_sender, permit_receiver) = channel::bounded(self.window);
+            (0..self.window).for_each(|_| permit_sender.send(()));
+
+            let (solution_sender, solution_receiver) =
+                channel::bounded::<(CaseIndex, thread::Result<S::Solution>)>(self.window);
 
             // Spawn a print thread which will do all the printing, bailing on an error.
             let print_thread = scope.spawn(move || {
-                // Solutions may arrive in any order; collect them into a hash table
+                // Solutions may arrive in any order; collect them into a hash
+                // table, and print them in the order of the selected cases.
                 let mut solutions = HashMap::new();
-                let mut next_case = CaseIndex::default();
-
-                for (case, solution) in receiver {
-                    if case == next_case {
-                        next_case = printer
-                            .print_advance(next_case, solution)
-                            .map_err(move |err| (next_case, err))?;
-
-                        while let Some(solution) = solutions.remove(&next_case) {
-                            next_case = printer
-                                .print_advance(next_case, solution)
-                                .map_err(move |err| (next_case, err))?;
+                let mut order = global_data
+                    .cases()
+                    .map(|(case, _)| case)
+                    .filter(|&case| settings.selection.contains(case));
+                let mut next_case = order.next();
+
+                // Print every solution that's ready, up to the first case
+                // which is still being solved. Cases in the journal are
+                // always ready, and don't hold a permit.
+                let mut print_ready = |solutions: &mut HashMap<_, _>| {
+                    while let Some(case) = next_case {
+                        if let Some(solution) = settings.replay(case) {
+                            printer
+                                .print_solution(case, solution)
+                                .map_err(|err| PrintFailure::Output(case, OutputError::Print(err)))?;
+                        } else {
+                            let solution = match solutions.remove(&case) {
+                                Some(solution) => solution,
+                                None => break,
+                            };
+
+                            settings
+                                .print(&mut printer, case, solution)
+                                .map_err(move |err| PrintFailure::Output(case, err))?;
+                            monitor.case_printed(case);
+                            permit_sender.send(());
                         }
-                    } else {
-                        solutions.insert(case, solution);
+                        next_case = order.next();
                     }
+                    Ok(())
+                };
+
+                print_ready(&mut solutions)?;
+
+                for (case, solution) in solution_receiver {
+                    solutions.insert(case, solution.map_err(PrintFailure::Panic)?);
+                    print_ready(&mut solutions)?;
                 }
                 Ok(())
             });
 
-            // Start spawning test cases
-            global_data.cases().try_for_each(move |(case, global_data)| {
-                // Can't use ? here, because the chain of ? confuses the type inferrer.
-                let case_data = match tokens.next() {
-                    Err(err) => return Err(CaseError::load_error(case, err)),
-                    Ok(case_data) => case_data,
-                };
-
-                let local_sender = sender.clone();
+            // Spawn the workers. Each one pulls cases until the case channel
+            // is closed, so this is the only parallelism in the executor.
+            for _ in 0..self.num_threads {
+                let case_receiver = case_receiver.clone();
+                let solution_sender = solution_sender.clone();
 
                 scope.spawn(move || {
-                    let solution = solver.solve_case(global_data, case_data);
-                    local_sender.send((case, solution));
+                    for (case, case_data) in case_receiver {
+                        // Once a solver has panicked, just drain the remaining cases
+                        if panicked.load(Ordering::Relaxed) {
+                            continue;
+                        }
+
+                        let solution = panic::catch_unwind(AssertUnwindSafe(|| {
+                            settings.solve(solver, case, &global_data.data, case_data, monitor)
+                        }));
+
+                        if solution.is_err() {
+                            panicked.store(true, Ordering::Relaxed);
+                        }
+
+                        solution_sender.send((case, solution));
+                    }
                 });
+            }
 
-                Ok(())
-            })?;
+            // The print thread finishes once every worker has dropped its sender
+            drop(solution_sender);
+
+            // Start sending test cases to the workers
+            settings
+                .load_cases(global_data, cases, monitor)
+                .take_while(|_| !panicked.load(Ordering::Relaxed))
+                // Cases in the journal are printed without being solved
+                .filter(|(case, case_data)| case_data.is_err() || settings.replay(*case).is_none())
+                // Wait for room in the window. If the print thread has failed,
+                // the permits run out, and loading stops.
+                .take_while(|_| permit_receiver.recv().is_some())
+                .try_for_each(move |(case, case_data)| {
+                    let case_data = case_data.map_err(|err| CaseError::load_error(case, err))?;
+
+                    case_sender.send((case, case_data));
+                    Ok(())
+                })?;
+
+            // If a solver panicked, resume the panic on this thread.
+            match print_thread.join().expect("Print thread panicked!") {
+                Ok(()) => Ok(()),
+                Err(PrintFailure::Output(case, err)) => Err(err.into_case_error(case)),
+                Err(PrintFailure::Panic(payload)) => panic::resume_unwind(payload),
+            }
+        });
 
-            print_thread
-                .join()
-                .expect("Print thread panicked!")
-                .map_err(|(case, err)| CaseError::print_error(case, err))?;
+        monitor.finish();
+        result
+    }
+}
 
-            // TODO: check other threads for panics
-            Ok(())
-        })
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+
+    use super::*;
+    use crate::printer::CollectingPrinter;
+    use crate::solver::solver;
+    use crate::tokens::TokensReader;
+
+    // Solve cases in a way which tempts a parallel executor to finish them out
+    // of order, tracking how many are in progress at once
+    fn run_threads(executor: ThreadExecutor, input: &str, busiest: &AtomicUsize) -> CollectingPrinter {
+        let in_progress = AtomicUsize::new(0);
+
+        let solver = solver(|n: u64| {
+            let running = in_progress.fetch_add(1, Ordering::SeqCst) + 1;
+            busiest.fetch_max(running, Ordering::SeqCst);
+
+            thread::sleep(Duration::from_millis(n));
+            in_progress.fetch_sub(1, Ordering::SeqCst);
+            n
+        });
+
+        let mut printer = CollectingPrinter::new();
+        executor.run(TokensReader::new(input.as_bytes()), &mut printer, solver);
+        printer
+    }
+
+    fn numbers(printer: &CollectingPrinter) -> Vec<(usize, &str)> {
+        printer
+            .solutions()
+            .iter()
+            .map(|(case, solution)| (case.number(), solution.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn threads_print_in_case_order() {
+        let busiest = AtomicUsize::new(0);
+        let printer = run_threads(ThreadExecutor::with_threads(8), "5 25 20 15 10 5", &busiest);
+
+        assert_eq!(numbers(&printer), [(1, "25"), (2, "20"), (3, "15"), (4, "10"), (5, "5")]);
+        assert!(busiest.load(Ordering::SeqCst) > 1);
+    }
+
+    #[test]
+    fn reorder_window_of_one_solves_one_case_at_a_time() {
+        let busiest = AtomicUsize::new(0);
+        let executor = ThreadExecutor::with_threads(8).with_reorder_window(1);
+        let printer = run_threads(executor, "5 25 20 15 10 5", &busiest);
+
+        assert_eq!(numbers(&printer), [(1, "25"), (2, "20"), (3, "15"), (4, "10"), (5, "5")]);
+        assert_eq!(busiest.load(Ordering::SeqCst), 1);
+    }
+
+    // A journal left by an earlier run which solved cases 1 and 3
+    fn earlier_journal(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("libcodejam-{}-{}.journal", name, process::id()));
+        let _ = fs::remove_file(&path);
+
+        let journal = Journal::open(&path).unwrap();
+        journal.record(CaseIndex::new(1).unwrap(), "one").unwrap();
+        journal.record(CaseIndex::new(3).unwrap(), "three").unwrap();
+        path
+    }
+
+    // Check that a run resumed from `earlier_journal` printed every case,
+    // only solved the cases which weren't recorded, and recorded them
+    fn check_resumed(printer: &CollectingPrinter, solved: Mutex<Vec<u32>>, path: &Path) {
+        let solutions: Vec<(usize, &str)> = printer
+            .solutions()
+            .iter()
+            .map(|(case, solution)| (case.number(), solution.as_str()))
+            .collect();
+        assert_eq!(solutions, [(1, "one"), (2, "20"), (3, "three"), (4, "40"), (5, "50")]);
+
+        let mut solved = solved.into_inner().unwrap();
+        solved.sort();
+        assert_eq!(solved, [2, 4, 5]);
+
+        let journal = Journal::open(path).unwrap();
+        assert_eq!(journal.len(), 5);
+        assert_eq!(journal.get(CaseIndex::new(4).unwrap()), Some("40"));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn sequential_executor_resumes_from_journal() {
+        let path = earlier_journal("sequential");
+        let solved = Mutex::new(Vec::new());
+        let solver = solver(|n: u32| {
+            solved.lock().unwrap().push(n);
+            n * 10
+        });
+
+        let mut printer = CollectingPrinter::new();
+        SequentialExecutor::new()
+            .journal(Journal::open(&path).unwrap())
+            .run(TokensReader::new("5 1 2 3 4 5".as_bytes()), &mut printer, solver);
+
+        check_resumed(&printer, solved, &path);
+    }
+
+    #[test]
+    fn thread_executor_resumes_from_journal() {
+        let path = earlier_journal("threads");
+        let solved = Mutex::new(Vec::new());
+        let solver = solver(|n: u32| {
+            solved.lock().unwrap().push(n);
+            n * 10
+        });
+
+        let mut printer = CollectingPrinter::new();
+        ThreadExecutor::with_threads(3)
+            .journal(Journal::open(&path).unwrap())
+            .run(TokensReader::new("5 1 2 3 4 5".as_bytes()), &mut printer, solver);
+
+        check_resumed(&printer, solved, &path);
+    }
+
+    // Check that a run with cases 2 and 4-5 selected only solved those cases,
+    // and printed them with their own case numbers
+    fn check_selected(printer: &CollectingPrinter, solved: Mutex<Vec<u32>>) {
+        assert_eq!(numbers(printer), [(2, "20"), (4, "40"), (5, "50")]);
+
+        let mut solved = solved.into_inner().unwrap();
+        solved.sort();
+        assert_eq!(solved, [2, 4, 5]);
+    }
+
+    #[test]
+    fn sequential_executor_skips_unselected_cases() {
+        let solved = Mutex::new(Vec::new());
+        let solver = solver(|n: u32| {
+            solved.lock().unwrap().push(n);
+            n * 10
+        });
+
+        let mut printer = CollectingPrinter::new();
+        SequentialExecutor::new()
+            .cases("2,4-5".parse().unwrap())
+            .run(TokensReader::new("5 1 2 3 4 5".as_bytes()), &mut printer, solver);
+
+        check_selected(&printer, solved);
+    }
+
+    #[test]
+    fn thread_executor_skips_unselected_cases() {
+        let solved = Mutex::new(Vec::new());
+        let solver = solver(|n: u32| {
+            solved.lock().unwrap().push(n);
+            n * 10
+        });
+
+        let mut printer = CollectingPrinter::new();
+        ThreadExecutor::with_threads(3)
+            .cases("2,4-5".parse().unwrap())
+            .run(TokensReader::new("5 1 2 3 4 5".as_bytes()), &mut printer, solver);
+
+        check_selected(&printer, solved);
     }
 }
@@ -0,0 +1,322 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::process::exit;
+
+use crate::case_index::{case_range, CaseIndex};
+use crate::data::{input_text, Group, LoadGlobalData, WriteGlobalData, WriteTokens};
+use crate::executor::ExecutionError;
+use crate::tokens::{Tokens, TokensReader};
+
+#[derive(Debug)]
+pub enum ExtractError<E1: Error, E2: Error> {
+    Load(ExecutionError<E1, E2>),
+    NoSuchCase { case: CaseIndex, num_cases: usize },
+}
+
+impl<E1: Error, E2: Error> Display for ExtractError<E1, E2> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ExtractError::Load(err) => err.fmt(f),
+            ExtractError::NoSuchCase { case, num_cases } => {
+                write!(f, "can't extract {}: the input only has {} cases", case, num_cases)
+            }
+        }
+    }
+}
+
+impl<E1: Error, E2: Error> Error for ExtractError<E1, E2> {
+    fn cause(&self) -> Option<&dyn Error> {
+        match self {
+            ExtractError::Load(err) => Some(err),
+            ExtractError::NoSuchCase { .. } => None,
+        }
+    }
+}
+
+/// Read a problem and write a new input containing only one of its cases,
+/// with a case count of 1 and the original global data, so that the case can
+/// be reproduced on its own. The cases before it are loaded and discarded.
+pub fn extract_case<G, C>(tokens: &mut impl Tokens, case: CaseIndex) -> Result<String, ExtractError<G::Err, C::Err>>
+    where
+        G: LoadGlobalData + WriteGlobalData,
+        C: Group + WriteTokens,
+{
+    let global_data = tokens
+        .start_problem::<G>()
+        .map_err(|err| ExtractError::Load(ExecutionError::global_error(err)))?;
+
+    let num_cases = global_data.num_cases;
+
+    for (current, _) in global_data.cases() {
+        let case_data = tokens
+            .next::<C>()
+            .map_err(|err| ExtractError::Load(ExecutionError::load_error(current, err)))?;
+
+        if current == case {
+            return Ok(input_text(&global_data.data, &[case_data]));
+        }
+    }
+
+    Err(ExtractError::NoSuchCase { case, num_cases })
+}
+
+/// How many lines each case of an input file takes up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseLines {
+    /// Every case is this many lines
+    Fixed(usize),
+    /// Every case starts with a line whose first token is the number of lines
+    /// which follow it, such as the row count of a grid
+    Counted,
+}
+
+/// The line layout of an input file, for extracting a case from it without
+/// the problem's types. The case count is the first token of its own line,
+/// with any global data on lines before or after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineLayout {
+    pub global_before: usize,
+    pub global_after: usize,
+    pub case_lines: CaseLines,
+}
+
+impl Default for LineLayout {
+    fn default() -> Self {
+        LineLayout {
+            global_before: 0,
+            global_after: 0,
+            case_lines: CaseLines::Fixed(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractLinesError {
+    /// The input ended partway through the global data or a case
+    Truncated,
+    /// A line which should start with a count doesn't
+    InvalidCount { line: usize },
+    NoSuchCase { case: CaseIndex, num_cases: usize },
+}
+
+impl Display for ExtractLinesError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ExtractLinesError::Truncated => write!(f, "the input ended partway through a case"),
+            ExtractLinesError::InvalidCount { line } => write!(f, "expected a count at the start of line {}", line),
+            ExtractLinesError::NoSuchCase { case, num_cases } => {
+                write!(f, "can't extract {}: the input only has {} cases", case, num_cases)
+            }
+        }
+    }
+}
+
+impl Error for ExtractLinesError {}
+
+// The count at the start of a line, given its 1-based line number
+fn leading_count(line: usize, text: &str) -> Result<usize, ExtractLinesError> {
+    text.split_whitespace()
+        .next()
+        .and_then(|token| token.parse().ok())
+        .ok_or(ExtractLinesError::InvalidCount { line })
+}
+
+/// Extract a case from the text of an input file, like `extract_case`, but
+/// by its line layout rather than by loading it. The lines of the global
+/// data and the case are copied as they are, and the first token of the case
+/// count line is replaced with 1.
+pub fn extract_case_lines(input: &str, layout: LineLayout, case: CaseIndex) -> Result<String, ExtractLinesError> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut next = 0;
+
+    // Take the next `count` lines, along with the line number of the first
+    let mut take = |count: usize| {
+        let taken = lines.get(next..next + count).ok_or(ExtractLinesError::Truncated)?;
+        next += count;
+        Ok((next - count + 1, taken))
+    };
+
+    let (_, global_before) = take(layout.global_before)?;
+    let (count_line, count_text) = take(1)?;
+    let num_cases = leading_count(count_line, count_text[0])?;
+    let (_, global_after) = take(layout.global_after)?;
+
+    for current in case_range(num_cases) {
+        let case_data = match layout.case_lines {
+            CaseLines::Fixed(len) => take(len)?.1,
+            CaseLines::Counted => {
+                let (line, first) = take(1)?;
+                let rest = take(leading_count(line, first[0])?)?.1;
+                &lines[line - 1..line + rest.len()]
+            }
+        };
+
+        if current == case {
+            let count_text = std::iter::once("1")
+                .chain(count_text[0].split_whitespace().skip(1))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            return Ok(global_before
+                .iter()
+                .chain(Some(&count_text.as_str()))
+                .chain(global_after)
+                .chain(case_data)
+                .map(|line| format!("{}\n", line))
+                .collect());
+        }
+    }
+
+    Err(ExtractLinesError::NoSuchCase { case, num_cases })
+}
+
+/// The whole `main` of a binary which extracts a case from an input file,
+/// for problems with global data `G` and case data `C`:
+///
+/// ```ignore
+/// fn main() {
+///     extract_cli::<(), Data>();
+/// }
+/// ```
+///
+/// Usage: `extract INPUT CASE [OUTPUT]`. The extracted input is written to
+/// OUTPUT, or to stdout.
+pub fn extract_cli<G, C>()
+    where
+        G: LoadGlobalData + WriteGlobalData,
+        C: Group + WriteTokens,
+{
+    const USAGE: &str = "usage: extract INPUT CASE [OUTPUT]";
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.len() < 2 || args.len() > 3 {
+        eprintln!("{}", USAGE);
+        exit(2);
+    }
+
+    let case = match args[1].parse().ok().and_then(CaseIndex::new) {
+        Some(case) => case,
+        None => {
+            eprintln!("invalid case number: {}\n{}", args[1], USAGE);
+            exit(2);
+        }
+    };
+
+    let input = File::open(&args[0]).unwrap_or_else(|err| {
+        eprintln!("error opening {}: {}", args[0], err);
+        exit(1);
+    });
+
+    let text = extract_case::<G, C>(&mut TokensReader::new(BufReader::new(input)), case)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        });
+
+    match args.get(2) {
+        None => print!("{}", text),
+        Some(path) => fs::write(path, text).unwrap_or_else(|err| {
+            eprintln!("error writing {}: {}", path, err);
+            exit(1);
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::CountPrefix;
+
+    fn extract<G, C>(input: &str, case: usize) -> Result<String, ExtractError<G::Err, C::Err>>
+        where
+            G: LoadGlobalData + WriteGlobalData,
+            C: Group + WriteTokens,
+    {
+        extract_case::<G, C>(&mut TokensReader::new(input.as_bytes()), CaseIndex::new(case).unwrap())
+    }
+
+    #[test]
+    fn extracts_one_case() {
+        let extracted = extract::<(), (u32, String)>("3\n1 a\n2 b\n3 c\n", 2).unwrap();
+        assert_eq!(extracted, "1\n2 b\n");
+    }
+
+    #[test]
+    fn keeps_global_data() {
+        let extracted = extract::<CountPrefix<u32>, u32>("2 100\n5\n6\n", 2).unwrap();
+
+        let mut tokens = TokensReader::new(extracted.as_bytes());
+        let global_data = tokens.start_problem::<CountPrefix<u32>>().unwrap();
+        assert_eq!((global_data.num_cases, global_data.data.0), (1, 100));
+        assert_eq!(tokens.next::<u32>().unwrap(), 6);
+    }
+
+    #[test]
+    fn reports_missing_cases() {
+        match extract::<(), u32>("2\n5\n6\n", 3) {
+            Err(ExtractError::NoSuchCase { case, num_cases }) => {
+                assert_eq!((case.number(), num_cases), (3, 2));
+            }
+            result => panic!("expected NoSuchCase, got {:?}", result),
+        }
+    }
+
+    fn extract_lines(input: &str, layout: LineLayout, case: usize) -> Result<String, ExtractLinesError> {
+        extract_case_lines(input, layout, CaseIndex::new(case).unwrap())
+    }
+
+    #[test]
+    fn extracts_fixed_lines_with_global_data() {
+        let layout = LineLayout {
+            global_after: 1,
+            case_lines: CaseLines::Fixed(2),
+            ..LineLayout::default()
+        };
+        let input = "3\n100 200\n2\n1 2\n3\n4  5 6\n1\n7\n";
+
+        assert_eq!(extract_lines(input, layout, 2).unwrap(), "1\n100 200\n3\n4  5 6\n");
+        assert_eq!(extract_lines("2 9\na\nb\n", LineLayout::default(), 1).unwrap(), "1 9\na\n");
+    }
+
+    #[test]
+    fn extracts_counted_lines() {
+        let layout = LineLayout {
+            global_before: 1,
+            case_lines: CaseLines::Counted,
+            ..LineLayout::default()
+        };
+        let input = "x\n3\n2 3\n...\n.#.\n0\n1 1\n#\n";
+
+        assert_eq!(extract_lines(input, layout, 1).unwrap(), "x\n1\n2 3\n...\n.#.\n");
+        assert_eq!(extract_lines(input, layout, 2).unwrap(), "x\n1\n0\n");
+        assert_eq!(extract_lines(input, layout, 3).unwrap(), "x\n1\n1 1\n#\n");
+    }
+
+    #[test]
+    fn reports_bad_line_layouts() {
+        let counted = LineLayout {
+            case_lines: CaseLines::Counted,
+            ..LineLayout::default()
+        };
+
+        assert_eq!(
+            extract_lines("2\na\nb\n", LineLayout::default(), 3),
+            Err(ExtractLinesError::NoSuchCase { case: CaseIndex::new(3).unwrap(), num_cases: 2 }),
+        );
+        assert_eq!(extract_lines("3\na\nb\n", LineLayout::default(), 3), Err(ExtractLinesError::Truncated));
+        assert_eq!(extract_lines("1\nx\n", counted, 1), Err(ExtractLinesError::InvalidCount { line: 2 }));
+        assert_eq!(extract_lines("", LineLayout::default(), 1), Err(ExtractLinesError::Truncated));
+    }
+
+    #[test]
+    fn reports_load_errors_before_the_case() {
+        match extract::<(), u32>("3\n5\nx\n7\n", 3) {
+            Err(ExtractError::Load(ExecutionError::Case(..))) => {}
+            result => panic!("expected a load error, got {:?}", result),
+        }
+    }
+}
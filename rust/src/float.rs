@@ -0,0 +1,254 @@
+use std::fmt::{self, Display, Formatter};
+
+use num_traits::Float;
+use ordered_float::{NotNan, OrderedFloat};
+
+use crate::solver::{MaybeImpossibleSolution, Solver};
+
+/// How floating point values in a solution should be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatStyle {
+    /// The shortest representation which reads back as the same value,
+    /// written without scientific notation
+    Plain,
+
+    /// A fixed number of digits after the decimal point
+    Fixed(usize),
+}
+
+/// A value which can be displayed with its floating point values written in
+/// a particular style. Other values are displayed as usual. Tuples, slices
+/// and vectors apply the style to each element, and separate them with
+/// spaces.
+///
+/// This is needed because the precision of a `Formatter` also truncates
+/// strings, so it can't be used to format a whole solution.
+pub trait DisplayFloat {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result;
+}
+
+impl<T: DisplayFloat + ?Sized> DisplayFloat for &T {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        (*self).fmt_float(f, style)
+    }
+}
+
+#[macro_export]
+macro_rules! display_float_via_display {
+    ( $( $type:ty )+ ) => {$(
+        impl $crate::float::DisplayFloat for $type {
+            fn fmt_float(&self, f: &mut std::fmt::Formatter, _style: $crate::float::FloatStyle) -> std::fmt::Result {
+                std::fmt::Display::fmt(self, f)
+            }
+        }
+    )*}
+}
+
+display_float_via_display!{
+    i8 i16 i32 i64 i128 isize
+    u8 u16 u32 u64 u128 usize
+    bool char String str
+}
+
+macro_rules! display_float_primitive {
+    ( $( $type:ty )+ ) => {$(
+        impl DisplayFloat for $type {
+            fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+                // Unlike `Debug`, `Display` for floats never uses scientific notation
+                match style {
+                    FloatStyle::Plain => write!(f, "{}", self),
+                    FloatStyle::Fixed(precision) => write!(f, "{:.*}", precision, self),
+                }
+            }
+        }
+    )*}
+}
+
+display_float_primitive!{f32 f64}
+
+impl<T: DisplayFloat + Float> DisplayFloat for OrderedFloat<T> {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.0.fmt_float(f, style)
+    }
+}
+
+impl<T: DisplayFloat + Float> DisplayFloat for NotNan<T> {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.into_inner().fmt_float(f, style)
+    }
+}
+
+impl<T: DisplayFloat> DisplayFloat for MaybeImpossibleSolution<T> {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        match self {
+            MaybeImpossibleSolution::Success(solution) => solution.fmt_float(f, style),
+            MaybeImpossibleSolution::Failure(msg) => f.write_str(msg),
+        }
+    }
+}
+
+macro_rules! tuple_display_float {
+    () => ();
+    ($field:ident $(, $tail:ident)*) => {
+        tuple_display_float!{$($tail),*}
+
+        #[allow(non_snake_case)]
+        impl< $field : DisplayFloat $(, $tail : DisplayFloat)* > DisplayFloat for ($field, $($tail,)*) {
+            fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+                let ($field, $($tail,)*) = self;
+                $field.fmt_float(f, style)?;
+                $(
+                    f.write_str(" ")?;
+                    $tail.fmt_float(f, style)?;
+                )*
+                Ok(())
+            }
+        }
+    }
+}
+
+tuple_display_float!{A, B, C, D, E, F, G, H, I, J, K, L}
+
+impl<T: DisplayFloat> DisplayFloat for [T] {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.iter().enumerate().try_for_each(|(index, item)| {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            item.fmt_float(f, style)
+        })
+    }
+}
+
+impl<T: DisplayFloat> DisplayFloat for Vec<T> {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.as_slice().fmt_float(f, style)
+    }
+}
+
+/// Adapter to `Display` a value with its floats written in a given style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Formatted<T> {
+    value: T,
+    style: FloatStyle,
+}
+
+impl<T> Formatted<T> {
+    pub fn new(value: T, style: FloatStyle) -> Self {
+        Formatted { value, style }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: DisplayFloat> Display for Formatted<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.value.fmt_float(f, self.style)
+    }
+}
+
+/// Display a value with `precision` digits after the decimal point of every float
+pub fn fixed<T: DisplayFloat>(value: T, precision: usize) -> Formatted<T> {
+    Formatted::new(value, FloatStyle::Fixed(precision))
+}
+
+/// Display a value with every float written without scientific notation
+pub fn plain<T: DisplayFloat>(value: T) -> Formatted<T> {
+    Formatted::new(value, FloatStyle::Plain)
+}
+
+#[derive(Debug)]
+pub struct FormattedSolver<S> {
+    underlying: S,
+    style: FloatStyle,
+}
+
+impl<S: Solver> Solver for FormattedSolver<S> where S::Solution: DisplayFloat {
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = Formatted<S::Solution>;
+
+    fn solve_case(
+        &self,
+        global_data: &Self::GlobalData,
+        case_data: Self::CaseData,
+    ) -> Self::Solution {
+        Formatted::new(self.underlying.solve_case(global_data, case_data), self.style)
+    }
+}
+
+/// Extension trait to choose how a solver's floats are printed
+pub trait FloatSolver: Solver + Sized where Self::Solution: DisplayFloat {
+    fn float_style(self, style: FloatStyle) -> FormattedSolver<Self> {
+        FormattedSolver {
+            underlying: self,
+            style,
+        }
+    }
+
+    fn fixed(self, precision: usize) -> FormattedSolver<Self> {
+        self.float_style(FloatStyle::Fixed(precision))
+    }
+
+    fn plain(self) -> FormattedSolver<Self> {
+        self.float_style(FloatStyle::Plain)
+    }
+}
+
+impl<S: Solver> FloatSolver for S where S::Solution: DisplayFloat {}
+
+// Whether a token was written as a float, rather than as an integer or a word
+fn is_float_token(token: &str) -> bool {
+    token.bytes().any(|byte| byte.is_ascii_digit())
+        && token.bytes().any(|byte| byte == b'.' || byte == b'e' || byte == b'E')
+        && token.bytes().all(|byte| byte.is_ascii_digit() || b"+-.eE".contains(&byte))
+        && token.parse::<f64>().is_ok()
+}
+
+/// Rewrite every float in some already displayed text in the given style,
+/// leaving everything else untouched. This is how printers apply a
+/// `FloatStyle` to solutions which are only known to be `Display`.
+///
+/// A float which was displayed without a decimal point, like `3` for `3.0`,
+/// can't be told apart from an integer and is left as it is; use
+/// `FloatSolver` where every float must be restyled.
+pub fn restyle_floats(text: &str, style: FloatStyle) -> String {
+    let mut restyled = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let token_start = rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len());
+        restyled.push_str(&rest[..token_start]);
+        rest = &rest[token_start..];
+
+        let token_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..token_end];
+
+        if is_float_token(token) {
+            let value: f64 = token.parse().unwrap();
+            restyled.push_str(&Formatted::new(value, style).to_string());
+        } else {
+            restyled.push_str(token);
+        }
+
+        rest = &rest[token_end..];
+    }
+
+    restyled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restyle_floats_only_rewrites_floats() {
+        assert_eq!(
+            restyle_floats("1.5 7 -2.25e1\nIMPOSSIBLE 1e-3  x1.5", FloatStyle::Fixed(2)),
+            "1.50 7 -22.50\nIMPOSSIBLE 0.00  x1.5",
+        );
+        assert_eq!(restyle_floats("2.5e-3 10", FloatStyle::Plain), "0.0025 10");
+    }
+}
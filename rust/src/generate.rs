@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+
+use rand::distributions::uniform::SampleUniform;
+use rand::distributions::{Distribution, Uniform};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+pub use rand::rngs::StdRng;
+
+pub fn seeded(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// A random integer in the inclusive range `low..=high`
+pub fn bounded<T: SampleUniform>(rng: &mut impl Rng, low: T, high: T) -> T {
+    Uniform::new_inclusive(low, high).sample(rng)
+}
+
+/// `count` random values in the inclusive range `low..=high`
+pub fn bounded_vec<T: SampleUniform>(rng: &mut impl Rng, count: usize, low: T, high: T) -> Vec<T> {
+    let range = Uniform::new_inclusive(low, high);
+    (0..count).map(|_| range.sample(rng)).collect()
+}
+
+/// A random permutation of `1..=n`. Like the other generators here, this
+/// numbers from 1, as most problem statements do.
+pub fn permutation(rng: &mut impl Rng, n: usize) -> Vec<usize> {
+    let mut values: Vec<usize> = (1..=n).collect();
+    values.shuffle(rng);
+    values
+}
+
+/// The `n - 1` edges of a random tree on the vertices `1..=n`
+pub fn tree(rng: &mut impl Rng, n: usize) -> Vec<(usize, usize)> {
+    let order = permutation(rng, n);
+
+    let mut edges: Vec<(usize, usize)> = (1..n)
+        .map(|i| (order[rng.gen_range(0, i)], order[i]))
+        .collect();
+
+    edges.shuffle(rng);
+    edges
+}
+
+/// `m` distinct edges forming a random connected simple graph on the
+/// vertices `1..=n`. `m` is clamped between `n - 1` and `n * (n - 1) / 2`.
+pub fn connected_graph(rng: &mut impl Rng, n: usize, m: usize) -> Vec<(usize, usize)> {
+    let max_edges = n * n.saturating_sub(1) / 2;
+    let m = m.max(n.saturating_sub(1)).min(max_edges);
+
+    let mut edges = tree(rng, n);
+    let mut seen: HashSet<(usize, usize)> = edges
+        .iter()
+        .map(|&(a, b)| (a.min(b), a.max(b)))
+        .collect();
+
+    while edges.len() < m {
+        let a = rng.gen_range(1, n + 1);
+        let b = rng.gen_range(1, n + 1);
+
+        if a != b && seen.insert((a.min(b), a.max(b))) {
+            edges.push((a, b));
+        }
+    }
+
+    edges.shuffle(rng);
+    edges
+}
+
+/// Generate `count` cases with a seeded RNG
+pub fn cases<C>(seed: u64, count: usize, mut generate: impl FnMut(&mut StdRng) -> C) -> Vec<C> {
+    let mut rng = seeded(seed);
+    (0..count).map(|_| generate(&mut rng)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Whether the edges connect all of the vertices `1..=n`
+    fn connected(n: usize, edges: &[(usize, usize)]) -> bool {
+        let mut reached = vec![false; n + 1];
+        let mut stack = vec![1];
+        reached[1] = true;
+
+        while let Some(vertex) = stack.pop() {
+            for &(a, b) in edges {
+                let next = match vertex {
+                    _ if vertex == a => b,
+                    _ if vertex == b => a,
+                    _ => continue,
+                };
+
+                if !reached[next] {
+                    reached[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        reached[1..].iter().all(|&reached| reached)
+    }
+
+    fn distinct(edges: &[(usize, usize)]) -> bool {
+        let unique: HashSet<(usize, usize)> = edges.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+        unique.len() == edges.len()
+    }
+
+    #[test]
+    fn tree_is_connected_with_n_minus_one_edges() {
+        let mut rng = seeded(1);
+
+        for n in 1..30 {
+            let edges = tree(&mut rng, n);
+
+            assert_eq!(edges.len(), n - 1);
+            assert!(edges.iter().all(|&(a, b)| a != b && a >= 1 && b >= 1 && a <= n && b <= n));
+            assert!(connected(n, &edges), "tree on {} vertices is disconnected: {:?}", n, edges);
+        }
+    }
+
+    #[test]
+    fn connected_graph_has_distinct_edges_and_is_connected() {
+        let mut rng = seeded(2);
+
+        for n in 2..15 {
+            for m in n - 1..=n * (n - 1) / 2 {
+                let edges = connected_graph(&mut rng, n, m);
+
+                assert_eq!(edges.len(), m);
+                assert!(edges.iter().all(|&(a, b)| a != b));
+                assert!(distinct(&edges), "repeated edge in {:?}", edges);
+                assert!(connected(n, &edges), "graph on {} vertices is disconnected: {:?}", n, edges);
+            }
+        }
+    }
+
+    #[test]
+    fn connected_graph_clamps_the_edge_count() {
+        let mut rng = seeded(3);
+
+        assert_eq!(connected_graph(&mut rng, 6, 0).len(), 5);
+        assert_eq!(connected_graph(&mut rng, 6, 100).len(), 15);
+        assert_eq!(connected_graph(&mut rng, 1, 5).len(), 0);
+        assert_eq!(connected_graph(&mut rng, 0, 5).len(), 0);
+    }
+
+    #[test]
+    fn seeded_generators_repeat() {
+        let first = cases(7, 5, |rng| permutation(rng, 8));
+        let second = cases(7, 5, |rng| permutation(rng, 8));
+
+        assert_eq!(first, second);
+    }
+}
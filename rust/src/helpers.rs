@@ -1,5 +1,7 @@
 use std::fmt::{self, Display, Formatter};
 
+use crate::float::{DisplayFloat, FloatStyle};
+
 #[derive(Debug, Clone)]
 pub struct SpacePair<A, B> {
     first: A,
@@ -28,3 +30,175 @@ macro_rules! space_sep {
     ($head:expr $(, $tail:expr)+) => ($crate::helpers::SpacePair::new($head, space_sep!($($tail),*)))
 }
 
+impl<A: DisplayFloat, B: DisplayFloat> DisplayFloat for SpacePair<A, B> {
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.first.fmt_float(f, style)?;
+        f.write_str(" ")?;
+        self.second.fmt_float(f, style)
+    }
+}
+
+/// Display adapter which writes each item of a sequence, separated by a
+/// separator. The items are iterated each time the value is displayed, so
+/// the sequence should be cheap to clone; pass `&vec` rather than `vec`.
+#[derive(Debug, Clone)]
+pub struct Join<I> {
+    items: I,
+    separator: &'static str,
+}
+
+/// Join the items of a sequence with a separator
+#[inline(always)]
+pub fn join<I>(items: I, separator: &'static str) -> Join<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: Display,
+{
+    Join { items, separator }
+}
+
+/// Join the items of a sequence with spaces
+#[inline(always)]
+pub fn spaced<I>(items: I) -> Join<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: Display,
+{
+    join(items, " ")
+}
+
+/// Write each item of a sequence on its own line. Use with `NewlinePrinter`
+/// to start the first item on the line after the case label.
+#[inline(always)]
+pub fn lines<I>(items: I) -> Join<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: Display,
+{
+    join(items, "\n")
+}
+
+impl<I> Display for Join<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: Display,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.items.clone().into_iter().enumerate().try_for_each(|(index, item)| {
+            if index > 0 {
+                f.write_str(self.separator)?;
+            }
+            item.fmt(f)
+        })
+    }
+}
+
+impl<I> DisplayFloat for Join<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: DisplayFloat,
+{
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.items.clone().into_iter().enumerate().try_for_each(|(index, item)| {
+            if index > 0 {
+                f.write_str(self.separator)?;
+            }
+            item.fmt_float(f, style)
+        })
+    }
+}
+
+/// Display adapter for a 2D grid, with each row on its own line and the
+/// cells of a row joined by a separator.
+#[derive(Debug, Clone)]
+pub struct JoinGrid<I> {
+    rows: I,
+    separator: &'static str,
+}
+
+/// Write a grid with its cells separated by `separator`; use `""` for boards
+/// of characters and `" "` for numbers.
+#[inline(always)]
+pub fn grid<I>(rows: I, separator: &'static str) -> JoinGrid<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: IntoIterator + Clone,
+        <I::Item as IntoIterator>::Item: Display,
+{
+    JoinGrid { rows, separator }
+}
+
+impl<I> Display for JoinGrid<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: IntoIterator + Clone,
+        <I::Item as IntoIterator>::Item: Display,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.rows.clone().into_iter().enumerate().try_for_each(|(index, row)| {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            join(row, self.separator).fmt(f)
+        })
+    }
+}
+
+impl<I> DisplayFloat for JoinGrid<I>
+    where
+        I: IntoIterator + Clone,
+        I::Item: IntoIterator + Clone,
+        <I::Item as IntoIterator>::Item: DisplayFloat,
+{
+    fn fmt_float(&self, f: &mut Formatter, style: FloatStyle) -> fmt::Result {
+        self.rows.clone().into_iter().enumerate().try_for_each(|(index, row)| {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            Join {
+                items: row,
+                separator: self.separator,
+            }.fmt_float(f, style)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::float::fixed;
+
+    #[test]
+    fn join_separates_items() {
+        let numbers = vec![1, 2, 3];
+        assert_eq!(join(&numbers, ", ").to_string(), "1, 2, 3");
+        assert_eq!(spaced(&numbers).to_string(), "1 2 3");
+        assert_eq!(lines(&numbers).to_string(), "1\n2\n3");
+        assert_eq!(spaced(Vec::<i32>::new()).to_string(), "");
+    }
+
+    #[test]
+    fn join_can_be_displayed_repeatedly() {
+        let words = spaced(&["a", "b"]);
+        assert_eq!(words.to_string(), "a b");
+        assert_eq!(words.to_string(), "a b");
+    }
+
+    #[test]
+    fn grid_writes_rows_on_lines() {
+        let board = vec![vec!['#', '.'], vec!['.', '#']];
+        assert_eq!(grid(&board, "").to_string(), "#.\n.#");
+
+        let numbers = vec![vec![1, 20], vec![300, 4]];
+        assert_eq!(grid(&numbers, " ").to_string(), "1 20\n300 4");
+    }
+
+    #[test]
+    fn adapters_apply_float_styles() {
+        let values = vec![0.5, 2.0];
+        assert_eq!(fixed(spaced(&values), 2).to_string(), "0.50 2.00");
+
+        let rows = vec![vec![1.0, 0.25], vec![0.125, 3.0]];
+        assert_eq!(fixed(grid(&rows, " "), 1).to_string(), "1.0 0.2\n0.1 3.0");
+    }
+}
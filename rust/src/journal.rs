@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str;
+use std::sync::Mutex;
+
+use crate::case_index::CaseIndex;
+
+/// A file of solved cases, so that a long run can be resumed after a crash.
+/// When an executor is given a journal, it replays the solutions already in
+/// the journal rather than solving those cases again, and appends each new
+/// solution once it has been printed.
+///
+/// Each record is a header line with the case number and the length of the
+/// solution in bytes, followed by the solution itself and a newline. An
+/// incomplete record at the end of the file, from a run which was killed
+/// while writing it, is discarded when the journal is opened.
+///
+/// The journal doesn't record which input it belongs to; use a separate
+/// journal for each input file.
+#[derive(Debug)]
+pub struct Journal {
+    solutions: HashMap<CaseIndex, String>,
+    file: Mutex<File>,
+}
+
+// Parse as many complete records as possible, returning them and the length
+// of the complete part of the journal
+fn parse_records(content: &[u8]) -> (HashMap<CaseIndex, String>, usize) {
+    let mut solutions = HashMap::new();
+    let mut position = 0;
+
+    loop {
+        let rest = &content[position..];
+
+        let header_end = match rest.iter().position(|&byte| byte == b'\n') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let header = match str::from_utf8(&rest[..header_end]) {
+            Ok(header) => header,
+            Err(..) => break,
+        };
+
+        let mut fields = header.split(' ');
+        let case = fields.next().and_then(|field| field.parse().ok()).and_then(CaseIndex::new);
+        let length: Option<usize> = fields.next().and_then(|field| field.parse().ok());
+
+        let (case, length) = match (case, length, fields.next()) {
+            (Some(case), Some(length), None) => (case, length),
+            _ => break,
+        };
+
+        let body = &rest[header_end + 1..];
+
+        if body.len() <= length || body[length] != b'\n' {
+            break;
+        }
+
+        let solution = match str::from_utf8(&body[..length]) {
+            Ok(solution) => solution,
+            Err(..) => break,
+        };
+
+        solutions.insert(case, solution.to_string());
+        position += header_end + 1 + length + 1;
+    }
+
+    (solutions, position)
+}
+
+impl Journal {
+    /// Open a journal, creating it if it doesn't exist, and load the
+    /// solutions already recorded in it.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            // Existing records are kept; any incomplete one is cut off below
+            .truncate(false)
+            .open(path)?;
+
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+
+        let (solutions, valid_length) = parse_records(&content);
+
+        // Drop any incomplete record, so that new records follow on cleanly
+        file.set_len(valid_length as u64)?;
+        file.seek(SeekFrom::End(0))?;
+
+        Ok(Journal {
+            solutions,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The recorded solution to a case, if it was solved in a previous run
+    pub fn get(&self, case: CaseIndex) -> Option<&str> {
+        self.solutions.get(&case).map(String::as_str)
+    }
+
+    pub fn contains(&self, case: CaseIndex) -> bool {
+        self.solutions.contains_key(&case)
+    }
+
+    /// The number of solutions recorded in previous runs
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+
+    /// Append a solution to the journal, flushing it to disk
+    pub fn record(&self, case: CaseIndex, solution: &str) -> io::Result<()> {
+        let mut record = format!("{} {}\n", case.number(), solution.len());
+        record.push_str(solution);
+        record.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(record.as_bytes())?;
+        file.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use super::*;
+
+    #[test]
+    fn incomplete_records_are_discarded() {
+        let path = env::temp_dir().join(format!("libcodejam-incomplete-{}.journal", process::id()));
+        fs::write(&path, "1 3\nabc\n2 5\nde").unwrap();
+
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(journal.len(), 1);
+        assert_eq!(journal.get(CaseIndex::new(1).unwrap()), Some("abc"));
+        assert!(!journal.contains(CaseIndex::new(2).unwrap()));
+
+        journal.record(CaseIndex::new(2).unwrap(), "a\nb").unwrap();
+        drop(journal);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1 3\nabc\n2 3\na\nb\n");
+        fs::remove_file(&path).unwrap();
+    }
+}
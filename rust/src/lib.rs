@@ -2,11 +2,34 @@
 #![feature(trusted_len)]
 #![feature(never_type)]
 
+#[cfg(feature = "futures")]
+pub mod async_executor;
+#[cfg(feature = "futures")]
+pub mod async_tokens;
+pub mod cancel;
 pub mod case_index;
+pub mod checker;
+pub mod cli;
+pub mod context;
+pub mod cross_check;
 pub mod data;
+pub mod debug;
 pub mod executor;
+pub mod extract;
+pub mod float;
+pub mod generate;
+pub mod journal;
+pub mod monitor;
+pub mod precompute;
 pub mod printer;
+pub mod progress;
+#[cfg(feature = "rayon")]
+pub mod rayon_executor;
+pub mod selection;
 pub mod solver;
+pub mod stress;
+pub mod testing;
+pub mod timing;
 pub mod tokens;
 pub mod helpers;
 
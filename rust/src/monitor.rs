@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use crate::case_index::CaseIndex;
+
+/// Hooks called by an executor as it runs a problem. With a parallel
+/// executor these are called from several threads at once, and cases may be
+/// loaded, solved and printed in different orders.
+pub trait Monitor: Sync {
+    /// Called before any cases are loaded, with the number of cases which
+    /// will be solved
+    fn start(&self, _num_cases: usize) {}
+
+    fn case_loaded(&self, _case: CaseIndex, _load_time: Duration) {}
+
+    fn case_solved(&self, _case: CaseIndex, _solve_time: Duration) {}
+
+    /// Called after `case_solved` if a case took longer than its time limit
+    fn case_timed_out(&self, _case: CaseIndex, _solve_time: Duration) {}
+
+    fn case_printed(&self, _case: CaseIndex) {}
+
+    /// Called once every case has been printed, or the run has failed
+    fn finish(&self) {}
+}
+
+impl Monitor for () {}
+
+impl<M: Monitor + ?Sized> Monitor for &M {
+    fn start(&self, num_cases: usize) {
+        (**self).start(num_cases)
+    }
+
+    fn case_loaded(&self, case: CaseIndex, load_time: Duration) {
+        (**self).case_loaded(case, load_time)
+    }
+
+    fn case_solved(&self, case: CaseIndex, solve_time: Duration) {
+        (**self).case_solved(case, solve_time)
+    }
+
+    fn case_timed_out(&self, case: CaseIndex, solve_time: Duration) {
+        (**self).case_timed_out(case, solve_time)
+    }
+
+    fn case_printed(&self, case: CaseIndex) {
+        (**self).case_printed(case)
+    }
+
+    fn finish(&self) {
+        (**self).finish()
+    }
+}
+
+impl<M: Monitor + ?Sized> Monitor for Box<M> {
+    fn start(&self, num_cases: usize) {
+        (**self).start(num_cases)
+    }
+
+    fn case_loaded(&self, case: CaseIndex, load_time: Duration) {
+        (**self).case_loaded(case, load_time)
+    }
+
+    fn case_solved(&self, case: CaseIndex, solve_time: Duration) {
+        (**self).case_solved(case, solve_time)
+    }
+
+    fn case_timed_out(&self, case: CaseIndex, solve_time: Duration) {
+        (**self).case_timed_out(case, solve_time)
+    }
+
+    fn case_printed(&self, case: CaseIndex) {
+        (**self).case_printed(case)
+    }
+
+    fn finish(&self) {
+        (**self).finish()
+    }
+}
+
+/// Several monitors, each of which is called in order
+impl<M: Monitor> Monitor for Vec<M> {
+    fn start(&self, num_cases: usize) {
+        self.iter().for_each(|monitor| monitor.start(num_cases))
+    }
+
+    fn case_loaded(&self, case: CaseIndex, load_time: Duration) {
+        self.iter().for_each(|monitor| monitor.case_loaded(case, load_time))
+    }
+
+    fn case_solved(&self, case: CaseIndex, solve_time: Duration) {
+        self.iter().for_each(|monitor| monitor.case_solved(case, solve_time))
+    }
+
+    fn case_timed_out(&self, case: CaseIndex, solve_time: Duration) {
+        self.iter().for_each(|monitor| monitor.case_timed_out(case, solve_time))
+    }
+
+    fn case_printed(&self, case: CaseIndex) {
+        self.iter().for_each(|monitor| monitor.case_printed(case))
+    }
+
+    fn finish(&self) {
+        self.iter().for_each(|monitor| monitor.finish())
+    }
+}
+
+impl<A: Monitor, B: Monitor> Monitor for (A, B) {
+    fn start(&self, num_cases: usize) {
+        self.0.start(num_cases);
+        self.1.start(num_cases);
+    }
+
+    fn case_loaded(&self, case: CaseIndex, load_time: Duration) {
+        self.0.case_loaded(case, load_time);
+        self.1.case_loaded(case, load_time);
+    }
+
+    fn case_solved(&self, case: CaseIndex, solve_time: Duration) {
+        self.0.case_solved(case, solve_time);
+        self.1.case_solved(case, solve_time);
+    }
+
+    fn case_timed_out(&self, case: CaseIndex, solve_time: Duration) {
+        self.0.case_timed_out(case, solve_time);
+        self.1.case_timed_out(case, solve_time);
+    }
+
+    fn case_printed(&self, case: CaseIndex) {
+        self.0.case_printed(case);
+        self.1.case_printed(case);
+    }
+
+    fn finish(&self) {
+        self.0.finish();
+        self.1.finish();
+    }
+}
@@ -0,0 +1,258 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::process::exit;
+
+use crate::data::{Group, LoadGlobalData};
+use crate::executor::{ExecutionError, Executor, SolverError};
+use crate::printer::Printer;
+use crate::solver::Solver;
+use crate::tokens::Tokens;
+
+/// A solver which needs to see every case before solving any of them; for
+/// instance, to build a sieve sized to the largest input in the problem.
+pub trait PrecomputeSolver {
+    type GlobalData;
+    type CaseData;
+    type Precomputed;
+    type Solution;
+
+    fn precompute(
+        &self,
+        global_data: &Self::GlobalData,
+        cases: &[Self::CaseData],
+    ) -> Self::Precomputed;
+
+    fn solve_case(
+        &self,
+        precomputed: &Self::Precomputed,
+        global_data: &Self::GlobalData,
+        case_data: Self::CaseData,
+    ) -> Self::Solution;
+}
+
+#[derive(Debug)]
+pub struct FnPrecomputeSolver<PF, F, GD, CD, P, S>
+    where
+        PF: Fn(&GD, &[CD]) -> P,
+        F: Fn(&P, &GD, CD) -> S,
+{
+    precompute_fn: PF,
+    solver_fn: F,
+    global_phantom: PhantomData<GD>,
+    case_phantom: PhantomData<CD>,
+    precomputed_phantom: PhantomData<P>,
+    solution_phantom: PhantomData<S>,
+}
+
+impl<PF, F, GD, CD, P, S> PrecomputeSolver for FnPrecomputeSolver<PF, F, GD, CD, P, S>
+    where
+        PF: Fn(&GD, &[CD]) -> P,
+        F: Fn(&P, &GD, CD) -> S,
+{
+    type GlobalData = GD;
+    type CaseData = CD;
+    type Precomputed = P;
+    type Solution = S;
+
+    fn precompute(&self, global_data: &GD, cases: &[CD]) -> P {
+        (self.precompute_fn)(global_data, cases)
+    }
+
+    fn solve_case(&self, precomputed: &P, global_data: &GD, case_data: CD) -> S {
+        (self.solver_fn)(precomputed, global_data, case_data)
+    }
+}
+
+pub fn precompute_solver<PF, F, GD, CD, P, S>(
+    precompute_fn: PF,
+    solver_fn: F,
+) -> FnPrecomputeSolver<PF, F, GD, CD, P, S>
+    where
+        PF: Fn(&GD, &[CD]) -> P,
+        F: Fn(&P, &GD, CD) -> S,
+{
+    FnPrecomputeSolver {
+        precompute_fn,
+        solver_fn,
+        global_phantom: PhantomData,
+        case_phantom: PhantomData,
+        precomputed_phantom: PhantomData,
+        solution_phantom: PhantomData,
+    }
+}
+
+/// A `PrecomputeSolver` paired with its precomputed data, which can be run
+/// by any `Executor` like an ordinary solver.
+#[derive(Debug)]
+pub struct Precomputed<S: PrecomputeSolver> {
+    solver: S,
+    precomputed: S::Precomputed,
+}
+
+impl<S: PrecomputeSolver> Precomputed<S> {
+    pub fn precomputed(&self) -> &S::Precomputed {
+        &self.precomputed
+    }
+}
+
+impl<S: PrecomputeSolver> Solver for Precomputed<S> {
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = S::Solution;
+
+    fn solve_case(
+        &self,
+        global_data: &Self::GlobalData,
+        case_data: Self::CaseData,
+    ) -> Self::Solution {
+        self.solver.solve_case(&self.precomputed, global_data, case_data)
+    }
+}
+
+pub trait PrecomputeExecutor<P: Printer, S: PrecomputeSolver>: Executor<P, Precomputed<S>>
+    where
+        S::Solution: Display,
+{
+    /// Load every case, run the precomputation, then solve the cases with
+    /// this executor.
+    fn execute_precomputed<T: Tokens>(
+        &self,
+        mut tokens: T,
+        printer: P,
+        solver: S,
+    ) -> Result<(), SolverError<Precomputed<S>>>
+        where
+            S::GlobalData: LoadGlobalData,
+            S::CaseData: Group,
+    {
+        let global_data = tokens.start_problem()?;
+
+        let cases = global_data
+            .cases()
+            .map(|(case, _)| {
+                tokens
+                    .next()
+                    .map_err(|err| ExecutionError::load_error(case, err))
+            })
+            .collect::<Result<Vec<S::CaseData>, _>>()?;
+
+        let precomputed = solver.precompute(&global_data.data, &cases);
+        let solver = Precomputed {
+            solver,
+            precomputed,
+        };
+
+        self.execute_cases(
+            &global_data,
+            cases.into_iter().map(Ok::<_, <S::CaseData as Group>::Err>),
+            printer,
+            solver,
+        ).map_err(ExecutionError::Case)
+    }
+
+    fn run_precomputed<T: Tokens>(&self, tokens: T, printer: P, solver: S)
+        where
+            S::GlobalData: LoadGlobalData,
+            S::CaseData: Group,
+    {
+        self.execute_precomputed(tokens, printer, solver)
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            })
+    }
+}
+
+impl<E, P, S> PrecomputeExecutor<P, S> for E
+    where
+        E: Executor<P, Precomputed<S>>,
+        P: Printer,
+        S: PrecomputeSolver,
+        S::Solution: Display,
+{}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::executor::{SequentialExecutor, ThreadExecutor};
+    use crate::printer::CollectingPrinter;
+    use crate::tokens::TokensReader;
+
+    const INPUT: &str = "5 3 1 4 1 5";
+
+    // Scale each case by the largest one, sleeping so that later cases finish
+    // first under a parallel executor
+    fn scale(largest: &u64, _global_data: &(), n: u64) -> u64 {
+        thread::sleep(Duration::from_millis(6 - n));
+        n * largest
+    }
+
+    fn check_scaled(seen: Mutex<Vec<u64>>, printer: CollectingPrinter) {
+        assert_eq!(seen.into_inner().unwrap(), [3, 1, 4, 1, 5]);
+
+        let solutions: Vec<(usize, String)> = printer
+            .into_solutions()
+            .into_iter()
+            .map(|(case, solution)| (case.number(), solution))
+            .collect();
+        let expected: Vec<(usize, String)> = [15, 5, 20, 5, 25]
+            .iter()
+            .enumerate()
+            .map(|(index, solution)| (index + 1, solution.to_string()))
+            .collect();
+        assert_eq!(solutions, expected);
+    }
+
+    #[test]
+    fn sequential_executor_precomputes_every_case() {
+        let seen = Mutex::new(Vec::new());
+        let solver = precompute_solver(
+            |_: &(), cases: &[u64]| {
+                seen.lock().unwrap().extend_from_slice(cases);
+                cases.iter().cloned().max().unwrap()
+            },
+            scale,
+        );
+
+        let mut printer = CollectingPrinter::new();
+        SequentialExecutor::new().run_precomputed(TokensReader::new(INPUT.as_bytes()), &mut printer, solver);
+
+        check_scaled(seen, printer);
+    }
+
+    #[test]
+    fn thread_executor_precomputes_every_case() {
+        let seen = Mutex::new(Vec::new());
+        let solver = precompute_solver(
+            |_: &(), cases: &[u64]| {
+                seen.lock().unwrap().extend_from_slice(cases);
+                cases.iter().cloned().max().unwrap()
+            },
+            scale,
+        );
+
+        let mut printer = CollectingPrinter::new();
+        ThreadExecutor::with_threads(4).run_precomputed(TokensReader::new(INPUT.as_bytes()), &mut printer, solver);
+
+        check_scaled(seen, printer);
+    }
+
+    #[test]
+    fn load_errors_stop_before_precomputing() {
+        let solver = precompute_solver(
+            |_: &(), _: &[u64]| -> u64 { panic!("precomputed despite a load error") },
+            scale,
+        );
+
+        let mut printer = CollectingPrinter::new();
+        let result = SequentialExecutor::new()
+            .execute_precomputed(TokensReader::new("3 1 x 2".as_bytes()), &mut printer, solver);
+
+        assert!(result.is_err());
+        assert!(printer.solutions().is_empty());
+    }
+}
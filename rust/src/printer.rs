@@ -1,7 +1,11 @@
-use std::fmt::Display;
-use std::io;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufWriter, Write};
+use std::str::FromStr;
 
 use crate::case_index::CaseIndex;
+use crate::float::{restyle_floats, FloatStyle};
 
 pub trait Printer {
     fn print_solution(&mut self, case: CaseIndex, solution: impl Display) -> io::Result<()>;
@@ -12,6 +16,47 @@ pub trait Printer {
     }
 }
 
+impl<P: Printer> Printer for &mut P {
+    fn print_solution(&mut self, case: CaseIndex, solution: impl Display) -> io::Result<()> {
+        (**self).print_solution(case, solution)
+    }
+}
+
+/// A printer which records each solution in memory, rather than writing it.
+/// Pass it to an executor by `&mut` to inspect the solutions afterwards.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectingPrinter {
+    solutions: Vec<(CaseIndex, String)>,
+}
+
+impl CollectingPrinter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn solutions(&self) -> &[(CaseIndex, String)] {
+        &self.solutions
+    }
+
+    pub fn into_solutions(self) -> Vec<(CaseIndex, String)> {
+        self.solutions
+    }
+
+    pub fn get(&self, case: CaseIndex) -> Option<&str> {
+        self.solutions
+            .iter()
+            .find(|(solved, _)| *solved == case)
+            .map(|(_, solution)| solution.as_str())
+    }
+}
+
+impl Printer for CollectingPrinter {
+    fn print_solution(&mut self, case: CaseIndex, solution: impl Display) -> io::Result<()> {
+        self.solutions.push((case, solution.to_string()));
+        Ok(())
+    }
+}
+
 macro_rules! printer_pattern {
 	($($printer:ident : $pattern:expr ;)+) => ($(
         #[derive(Debug)]
@@ -43,3 +88,247 @@ printer_pattern! {
     StandardPrinter: "{case}: {solution}";
     NewlinePrinter: "{case}:\n{solution}";
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Case,
+    Solution,
+    Separator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    UnknownPlaceholder(String),
+    Unclosed,
+    UnmatchedBrace,
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{}}}", name),
+            TemplateError::Unclosed => write!(f, "placeholder is missing a closing }}"),
+            TemplateError::UnmatchedBrace => write!(f, "unmatched }} (use }}}} for a literal brace)"),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+/// The layout of each printed solution, parsed from a string like
+/// `"Case #{case}:{sep}{solution}"`. The placeholders are:
+///
+/// - `{case}`: the case number
+/// - `{solution}`: the solution
+/// - `{sep}`: the separator, which defaults to a single space
+///
+/// Literal braces are written as `{{` and `}}`. A newline is written after
+/// each solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+    separator: Cow<'static, str>,
+    float_style: Option<FloatStyle>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedBrace),
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or(TemplateError::Unclosed)?;
+                    let segment = match &rest[..end] {
+                        "case" => Segment::Case,
+                        "solution" => Segment::Solution,
+                        "sep" => Segment::Separator,
+                        name => return Err(TemplateError::UnknownPlaceholder(name.to_string())),
+                    };
+                    chars = rest[end + 1..].chars();
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(literal.split_off(0)));
+                    }
+                    segments.push(segment);
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Template {
+            segments,
+            separator: Cow::Borrowed(" "),
+            float_style: None,
+        })
+    }
+
+    /// Set the text used for `{sep}`
+    pub fn separator(self, separator: impl Into<Cow<'static, str>>) -> Self {
+        Template {
+            separator: separator.into(),
+            ..self
+        }
+    }
+
+    /// Rewrite the floats in each solution in a given style; see
+    /// `restyle_floats` for which tokens count as floats
+    pub fn float_style(self, float_style: FloatStyle) -> Self {
+        Template {
+            float_style: Some(float_style),
+            ..self
+        }
+    }
+
+    /// Write the floats in each solution with `precision` digits after the
+    /// decimal point
+    pub fn fixed(self, precision: usize) -> Self {
+        self.float_style(FloatStyle::Fixed(precision))
+    }
+
+    /// `Case #x: solution`, as used by Code Jam and Kick Start
+    pub fn code_jam() -> Self {
+        Template::parse("Case #{case}:{sep}{solution}").unwrap()
+    }
+
+    /// `Case #x:`, with the solution starting on the next line
+    pub fn code_jam_multiline() -> Self {
+        Template::code_jam().separator("\n")
+    }
+
+    /// Just the solution, without any case label
+    pub fn plain() -> Self {
+        Template::parse("{solution}").unwrap()
+    }
+
+    pub fn write(&self, mut writer: impl Write, case: CaseIndex, solution: impl Display) -> io::Result<()> {
+        self.segments.iter().try_for_each(|segment| match segment {
+            Segment::Literal(text) => writer.write_all(text.as_bytes()),
+            Segment::Case => write!(writer, "{}", case.number()),
+            Segment::Solution => match self.float_style {
+                Some(style) => writer.write_all(restyle_floats(&solution.to_string(), style).as_bytes()),
+                None => write!(writer, "{}", solution),
+            },
+            Segment::Separator => writer.write_all(self.separator.as_bytes()),
+        })?;
+        writer.write_all(b"\n")
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Self, TemplateError> {
+        Template::parse(template)
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template::code_jam()
+    }
+}
+
+/// A printer which lays out each solution according to a `Template`
+#[derive(Debug)]
+pub struct TemplatePrinter<W: Write> {
+    writer: BufWriter<W>,
+    template: Template,
+}
+
+impl<W: Write> TemplatePrinter<W> {
+    pub fn new(writer: W, template: Template) -> Self {
+        TemplatePrinter {
+            writer: BufWriter::new(writer),
+            template,
+        }
+    }
+
+    /// A printer which writes only the solutions, without case labels
+    pub fn unlabeled(writer: W) -> Self {
+        Self::new(writer, Template::plain())
+    }
+
+    pub fn template(&self) -> &Template {
+        &self.template
+    }
+}
+
+impl TemplatePrinter<io::Stdout> {
+    pub fn stdout(template: Template) -> Self {
+        Self::new(io::stdout(), template)
+    }
+}
+
+impl<W: Write> Printer for TemplatePrinter<W> {
+    fn print_solution(&mut self, case: CaseIndex, solution: impl Display) -> io::Result<()> {
+        self.template.write(&mut self.writer, case, solution)?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &Template, case: usize, solution: &str) -> String {
+        let mut output = Vec::new();
+        template.write(&mut output, CaseIndex::new(case).unwrap(), solution).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn template_presets() {
+        assert_eq!(render(&Template::code_jam(), 3, "42"), "Case #3: 42\n");
+        assert_eq!(render(&Template::code_jam_multiline(), 3, "1 2\n3 4"), "Case #3:\n1 2\n3 4\n");
+        assert_eq!(render(&Template::plain(), 3, "42"), "42\n");
+        assert_eq!(Template::default(), Template::code_jam());
+    }
+
+    #[test]
+    fn template_parsing() {
+        let template: Template = "{{{case}}} {solution}{sep}end".parse().unwrap();
+        assert_eq!(render(&template.separator("|"), 1, "x"), "{1} x|end\n");
+
+        assert_eq!(
+            Template::parse("{case} {answer}"),
+            Err(TemplateError::UnknownPlaceholder("answer".to_string())),
+        );
+        assert_eq!(Template::parse("Case {case"), Err(TemplateError::Unclosed));
+        assert_eq!(Template::parse("Case }"), Err(TemplateError::UnmatchedBrace));
+    }
+
+    #[test]
+    fn template_printer_writes_each_solution() {
+        let mut output = Vec::new();
+        {
+            let mut printer = TemplatePrinter::new(&mut output, Template::code_jam());
+            printer.print_solution(CaseIndex::new(1).unwrap(), "yes").unwrap();
+            printer.print_solution(CaseIndex::new(2).unwrap(), "no").unwrap();
+        }
+        assert_eq!(String::from_utf8(output).unwrap(), "Case #1: yes\nCase #2: no\n");
+    }
+
+    #[test]
+    fn template_float_style() {
+        let template = Template::code_jam().fixed(3);
+        assert_eq!(render(&template, 2, "0.5 4 1e-4"), "Case #2: 0.500 4 0.000\n");
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::case_index::CaseIndex;
+use crate::monitor::Monitor;
+
+#[derive(Debug)]
+struct ProgressState {
+    num_cases: usize,
+    loaded: usize,
+    solved: usize,
+    printed: usize,
+    started: Instant,
+    rendered: Option<Instant>,
+}
+
+/// A monitor which draws a progress line on stderr, with the number of cases
+/// loaded, solved and printed, and an estimate of the time remaining. Only
+/// counts are tracked, so cases may complete in any order.
+#[derive(Debug)]
+pub struct Progress {
+    state: Mutex<ProgressState>,
+    interval: Duration,
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+impl ProgressState {
+    fn eta(&self, now: Instant) -> Option<Duration> {
+        if self.solved == 0 {
+            return None;
+        }
+
+        let remaining = self.num_cases.saturating_sub(self.solved);
+        Some(now.duration_since(self.started).mul_f64(remaining as f64 / self.solved as f64))
+    }
+
+    fn line(&self, now: Instant) -> String {
+        let eta = match self.eta(now) {
+            Some(eta) => format_duration(eta),
+            None => "?".to_string(),
+        };
+
+        format!(
+            "{}/{} cases | loaded {} | solved {} | printed {} | {} elapsed | ETA {}",
+            self.printed,
+            self.num_cases,
+            self.loaded,
+            self.solved,
+            self.printed,
+            format_duration(now.duration_since(self.started)),
+            eta,
+        )
+    }
+
+    fn render(&mut self, now: Instant) {
+        self.rendered = Some(now);
+
+        // Progress is best-effort; there's nowhere to report a failure to
+        // write to stderr.
+        let _ = write!(io::stderr(), "\r{}  ", self.line(now));
+    }
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Progress {
+            state: Mutex::new(ProgressState {
+                num_cases: 0,
+                loaded: 0,
+                solved: 0,
+                printed: 0,
+                started: Instant::now(),
+                rendered: None,
+            }),
+            interval: Duration::from_millis(100),
+        }
+    }
+
+    /// Set the minimum time between redraws of the progress line
+    pub fn interval(self, interval: Duration) -> Self {
+        Progress { interval, ..self }
+    }
+
+    fn update(&self, update: impl FnOnce(&mut ProgressState)) {
+        let mut state = self.state.lock().unwrap();
+        update(&mut state);
+
+        let now = Instant::now();
+        let due = match state.rendered {
+            Some(rendered) => now.duration_since(rendered) >= self.interval,
+            None => true,
+        };
+
+        if due {
+            state.render(now);
+        }
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor for Progress {
+    fn start(&self, num_cases: usize) {
+        self.update(|state| {
+            state.num_cases = num_cases;
+            state.started = Instant::now();
+        })
+    }
+
+    fn case_loaded(&self, _case: CaseIndex, _load_time: Duration) {
+        self.update(|state| state.loaded += 1)
+    }
+
+    fn case_solved(&self, _case: CaseIndex, _solve_time: Duration) {
+        self.update(|state| state.solved += 1)
+    }
+
+    fn case_printed(&self, _case: CaseIndex) {
+        self.update(|state| state.printed += 1)
+    }
+
+    fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.render(Instant::now());
+        eprintln!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(num_cases: usize, solved: usize, now: Instant, elapsed: Duration) -> ProgressState {
+        ProgressState {
+            num_cases,
+            loaded: num_cases,
+            solved,
+            printed: solved / 2,
+            started: now - elapsed,
+            rendered: None,
+        }
+    }
+
+    #[test]
+    fn durations_are_formatted_as_clock_times() {
+        assert_eq!(format_duration(Duration::from_millis(900)), "0:00");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1:05");
+        assert_eq!(format_duration(Duration::from_secs(3599)), "59:59");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+    }
+
+    #[test]
+    fn eta_scales_the_elapsed_time_by_the_cases_remaining() {
+        let now = Instant::now();
+
+        assert_eq!(state(10, 0, now, Duration::from_secs(5)).eta(now), None);
+        assert_eq!(state(10, 2, now, Duration::from_secs(10)).eta(now), Some(Duration::from_secs(40)));
+        assert_eq!(state(10, 10, now, Duration::from_secs(10)).eta(now), Some(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn line_shows_counts_and_times() {
+        let now = Instant::now();
+
+        assert_eq!(
+            state(10, 4, now, Duration::from_secs(20)).line(now),
+            "2/10 cases | loaded 10 | solved 4 | printed 2 | 0:20 elapsed | ETA 0:30",
+        );
+        assert_eq!(
+            state(3, 0, now, Duration::from_secs(1)).line(now),
+            "0/3 cases | loaded 3 | solved 0 | printed 0 | 0:01 elapsed | ETA ?",
+        );
+    }
+}
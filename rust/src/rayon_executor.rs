@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+use rayon::prelude::*;
+
+use crate::case_index::CaseIndex;
+use crate::data::GlobalData;
+use crate::executor::{CaseError, Executor, ExecutorSettings, Settings};
+use crate::monitor::Monitor;
+use crate::printer::Printer;
+use crate::solver::Solver;
+
+/// An executor which loads every case, then solves them in parallel on the
+/// current rayon thread pool, which is the global pool unless the executor
+/// is run inside `ThreadPool::install`. Solvers which use rayon themselves
+/// share the same pool, so they don't oversubscribe the CPUs the way they
+/// would under a `ThreadExecutor`.
+///
+/// Unlike `ThreadExecutor`, no solution is printed until every case is solved.
+#[derive(Debug, Clone, Default)]
+pub struct RayonExecutor<M = ()> {
+    monitor: M,
+    settings: Settings,
+}
+
+impl RayonExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M> RayonExecutor<M> {
+    /// Report the progress of each run to a monitor
+    pub fn monitor<M2: Monitor>(self, monitor: M2) -> RayonExecutor<M2> {
+        RayonExecutor {
+            monitor,
+            settings: self.settings,
+        }
+    }
+}
+
+impl<M> ExecutorSettings for RayonExecutor<M> {
+    fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+}
+
+impl<P: Printer, S: Solver + Sync, M: Monitor> Executor<P, S> for RayonExecutor<M>
+    where
+        S::GlobalData: Sync,
+        S::CaseData: Send,
+        S::Solution: Display + Send,
+{
+    fn execute_cases<E: Error, I: Iterator<Item = Result<S::CaseData, E>>>(
+        &self,
+        global_data: &GlobalData<S::GlobalData>,
+        cases: I,
+        mut printer: P,
+        solver: S,
+    ) -> Result<(), CaseError<E>> {
+        let solver = &solver;
+        let monitor = &self.monitor;
+        let settings = &self.settings;
+
+        monitor.start(settings.count_pending(global_data.num_cases));
+
+        // Load cases up to the first load error. The cases before the error
+        // are still solved and printed, as they would be by the other executors.
+        let mut load_error = None;
+        let loaded: Vec<(CaseIndex, S::CaseData)> = settings
+            .load_cases(global_data, cases, monitor)
+            .filter(|(case, case_data)| case_data.is_err() || settings.replay(*case).is_none())
+            .scan(&mut load_error, |load_error, (case, case_data)| match case_data {
+                Ok(case_data) => Some((case, case_data)),
+                Err(err) => {
+                    **load_error = Some((case, err));
+                    None
+                }
+            })
+            .collect();
+
+        // A panic in a solver is resumed on this thread by rayon
+        let mut solutions: HashMap<CaseIndex, S::Solution> = loaded
+            .into_par_iter()
+            .map(|(case, case_data)| {
+                let solution = settings.solve(solver, case, &global_data.data, case_data, monitor);
+                (case, solution)
+            })
+            .collect();
+
+        let last_case = load_error.as_ref().map(|&(case, _)| case);
+
+        let result = global_data
+            .cases()
+            .map(|(case, _)| case)
+            .filter(|&case| settings.selection.contains(case))
+            .take_while(|&case| last_case.map_or(true, |last_case| case < last_case))
+            .try_for_each(|case| match settings.replay(case) {
+                Some(solution) => printer
+                    .print_solution(case, solution)
+                    .map_err(|err| CaseError::print_error(case, err)),
+                None => {
+                    let solution = solutions.remove(&case).expect("Case was never solved");
+
+                    settings
+                        .print(&mut printer, case, solution)
+                        .map_err(|err| err.into_case_error(case))?;
+                    monitor.case_printed(case);
+                    Ok(())
+                }
+            })
+            .and_then(|()| match load_error {
+                Some((case, err)) => Err(CaseError::load_error(case, err)),
+                None => Ok(()),
+            });
+
+        monitor.finish();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::executor::ExecutionError;
+    use crate::journal::Journal;
+    use crate::printer::CollectingPrinter;
+    use crate::solver::solver;
+    use crate::tokens::TokensReader;
+
+    fn numbers(printer: &CollectingPrinter) -> Vec<(usize, &str)> {
+        printer
+            .solutions()
+            .iter()
+            .map(|(case, solution)| (case.number(), solution.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn prints_in_case_order() {
+        // Later cases finish first
+        let solver = solver(|n: u64| {
+            thread::sleep(Duration::from_millis(n));
+            n
+        });
+
+        let mut printer = CollectingPrinter::new();
+        RayonExecutor::new().run(TokensReader::new("5 25 20 15 10 5".as_bytes()), &mut printer, solver);
+
+        assert_eq!(numbers(&printer), [(1, "25"), (2, "20"), (3, "15"), (4, "10"), (5, "5")]);
+    }
+
+    #[test]
+    fn stops_at_the_first_load_error() {
+        let solver = solver(|n: u32| n * 10);
+
+        let mut printer = CollectingPrinter::new();
+        let result = RayonExecutor::new().execute(TokensReader::new("4 1 2 x 4".as_bytes()), &mut printer, solver);
+
+        match result {
+            Err(ExecutionError::Case(..)) => {}
+            result => panic!("expected a load error, got {:?}", result),
+        }
+        assert_eq!(numbers(&printer), [(1, "10"), (2, "20")]);
+    }
+
+    #[test]
+    fn skips_unselected_cases() {
+        let solved = Mutex::new(Vec::new());
+        let solver = solver(|n: u32| {
+            solved.lock().unwrap().push(n);
+            n * 10
+        });
+
+        let mut printer = CollectingPrinter::new();
+        RayonExecutor::new()
+            .cases("2,4-5".parse().unwrap())
+            .run(TokensReader::new("5 1 2 3 4 5".as_bytes()), &mut printer, solver);
+
+        assert_eq!(numbers(&printer), [(2, "20"), (4, "40"), (5, "50")]);
+
+        let mut solved = solved.into_inner().unwrap();
+        solved.sort();
+        assert_eq!(solved, [2, 4, 5]);
+    }
+
+    #[test]
+    fn resumes_from_journal() {
+        let path = env::temp_dir().join(format!("libcodejam-rayon-{}.journal", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let journal = Journal::open(&path).unwrap();
+        journal.record(CaseIndex::new(1).unwrap(), "one").unwrap();
+        journal.record(CaseIndex::new(3).unwrap(), "three").unwrap();
+
+        let solved = Mutex::new(Vec::new());
+        let solver = solver(|n: u32| {
+            solved.lock().unwrap().push(n);
+            n * 10
+        });
+
+        let mut printer = CollectingPrinter::new();
+        RayonExecutor::new()
+            .journal(Journal::open(&path).unwrap())
+            .run(TokensReader::new("4 1 2 3 4".as_bytes()), &mut printer, solver);
+
+        assert_eq!(numbers(&printer), [(1, "one"), (2, "20"), (3, "three"), (4, "40")]);
+
+        let mut solved = solved.into_inner().unwrap();
+        solved.sort();
+        assert_eq!(solved, [2, 4]);
+
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(journal.len(), 4);
+        assert_eq!(journal.get(CaseIndex::new(2).unwrap()), Some("20"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::case_index::{case_range, CaseIndex};
+
+/// Which cases an executor should solve. Cases which aren't selected are
+/// still loaded, because the input has to be read in order, but they aren't
+/// solved or printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaseSelection {
+    All,
+    /// Only the cases with numbers in these ranges
+    Only(Vec<RangeInclusive<usize>>),
+}
+
+impl CaseSelection {
+    pub fn single(case: usize) -> Self {
+        CaseSelection::Only(vec![case..=case])
+    }
+
+    pub fn range(cases: RangeInclusive<usize>) -> Self {
+        CaseSelection::Only(vec![cases])
+    }
+
+    pub fn contains(&self, case: CaseIndex) -> bool {
+        match self {
+            CaseSelection::All => true,
+            CaseSelection::Only(ranges) => ranges.iter().any(|range| range.contains(&case.number())),
+        }
+    }
+
+    /// How many of the first `num_cases` cases are selected
+    pub fn count(&self, num_cases: usize) -> usize {
+        case_range(num_cases).filter(|&case| self.contains(case)).count()
+    }
+}
+
+impl Default for CaseSelection {
+    fn default() -> Self {
+        CaseSelection::All
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSelectionError {
+    part: String,
+}
+
+impl Display for ParseSelectionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid case selection {:?}: expected a case number like 7 or a range like 3-5",
+            self.part
+        )
+    }
+}
+
+impl Error for ParseSelectionError {}
+
+/// Parse a comma-separated list of case numbers and ranges, like `1,4,10-12`
+impl FromStr for CaseSelection {
+    type Err = ParseSelectionError;
+
+    fn from_str(spec: &str) -> Result<Self, ParseSelectionError> {
+        spec.split(',')
+            .map(str::trim)
+            .map(|part| {
+                let error = || ParseSelectionError {
+                    part: part.to_string(),
+                };
+
+                let (start, end) = match part.find('-') {
+                    Some(dash) => (&part[..dash], &part[dash + 1..]),
+                    None => (part, part),
+                };
+
+                let start: usize = start.trim().parse().map_err(|_| error())?;
+                let end: usize = end.trim().parse().map_err(|_| error())?;
+
+                if start == 0 || end < start {
+                    Err(error())
+                } else {
+                    Ok(start..=end)
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(CaseSelection::Only)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(spec: &str) -> String {
+        spec.parse::<CaseSelection>().unwrap_err().part
+    }
+
+    #[test]
+    fn single_case() {
+        assert_eq!("7".parse(), Ok(CaseSelection::single(7)));
+    }
+
+    #[test]
+    fn list_of_cases_and_ranges() {
+        let selection: CaseSelection = "1,4, 10-12".parse().unwrap();
+
+        assert_eq!(selection, CaseSelection::Only(vec![1..=1, 4..=4, 10..=12]));
+        assert_eq!(selection.count(20), 5);
+        assert_eq!(selection.count(11), 4);
+        assert!(selection.contains(CaseIndex::new(11).unwrap()));
+        assert!(!selection.contains(CaseIndex::new(5).unwrap()));
+    }
+
+    #[test]
+    fn case_zero_is_invalid() {
+        assert_eq!(parse_error("0"), "0");
+        assert_eq!(parse_error("3,0-2"), "0-2");
+    }
+
+    #[test]
+    fn backwards_range_is_invalid() {
+        assert_eq!(parse_error("5-3"), "5-3");
+    }
+
+    #[test]
+    fn empty_selection_is_invalid() {
+        assert_eq!(parse_error(""), "");
+        assert_eq!(parse_error("1,,2"), "");
+        assert_eq!(parse_error("x"), "x");
+    }
+
+    #[test]
+    fn all_cases_are_selected_by_default() {
+        assert_eq!(CaseSelection::default(), CaseSelection::All);
+        assert_eq!(CaseSelection::default().count(3), 3);
+    }
+}
@@ -1,6 +1,14 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt::{self, Display, Formatter};
 use std::marker::PhantomData;
 
+use thread_local::ThreadLocal;
+
+use crate::cancel::Cancellation;
+use crate::case_index::CaseIndex;
+use crate::context;
+
 pub trait Solver {
     type GlobalData;
     type CaseData;
@@ -11,6 +19,173 @@ pub trait Solver {
         global_data: &Self::GlobalData,
         case_data: Self::CaseData,
     ) -> Self::Solution;
+
+    /// Transform the solutions returned by this solver
+    fn map<T, F: Fn(Self::Solution) -> T>(self, map_fn: F) -> MapSolution<Self, F>
+        where Self: Sized
+    {
+        MapSolution {
+            underlying: self,
+            map_fn,
+        }
+    }
+
+    /// Transform case data before it is passed to this solver
+    fn map_input<CD, F: Fn(CD) -> Self::CaseData>(self, map_fn: F) -> MapInput<Self, F, CD>
+        where Self: Sized
+    {
+        MapInput {
+            underlying: self,
+            map_fn,
+            case_phantom: PhantomData,
+        }
+    }
+
+    /// Adapt a solver taking `(CaseIndex, CaseData)` to one taking only the
+    /// `CaseData`, filling in the index of the case being solved.
+    ///
+    /// # Panics
+    ///
+    /// The index comes from the case context, which only executors set, so
+    /// calling the new solver's `solve_case` directly panics. Call it through
+    /// `executor::solve_case` instead, which sets the context.
+    fn with_case_index<CD>(self) -> WithCaseIndex<Self>
+        where Self: Solver<CaseData = (CaseIndex, CD)> + Sized
+    {
+        WithCaseIndex { underlying: self }
+    }
+
+    /// Adapt a solver taking `(Cancellation, CaseData)` to one taking only
+    /// the `CaseData`, passing the cancellation token for the case's time
+    /// limit.
+    fn with_cancellation<CD>(self) -> WithCancellation<Self>
+        where Self: Solver<CaseData = (Cancellation, CD)> + Sized
+    {
+        WithCancellation { underlying: self }
+    }
+
+    /// Call a function with each case's data before it is solved
+    fn inspect_input<F: Fn(&Self::CaseData)>(self, inspect_fn: F) -> InspectInput<Self, F>
+        where Self: Sized
+    {
+        InspectInput {
+            underlying: self,
+            inspect_fn,
+        }
+    }
+
+    /// Call a function with each solution before it is returned
+    fn inspect<F: Fn(&Self::Solution)>(self, inspect_fn: F) -> Inspect<Self, F>
+        where Self: Sized
+    {
+        Inspect {
+            underlying: self,
+            inspect_fn,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MapSolution<S, F> {
+    underlying: S,
+    map_fn: F,
+}
+
+impl<T, S: Solver, F: Fn(S::Solution) -> T> Solver for MapSolution<S, F> {
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = T;
+
+    fn solve_case(&self, global_data: &S::GlobalData, case_data: S::CaseData) -> T {
+        (self.map_fn)(self.underlying.solve_case(global_data, case_data))
+    }
+}
+
+#[derive(Debug)]
+pub struct MapInput<S, F, CD> {
+    underlying: S,
+    map_fn: F,
+    case_phantom: PhantomData<CD>,
+}
+
+impl<CD, S: Solver, F: Fn(CD) -> S::CaseData> Solver for MapInput<S, F, CD> {
+    type GlobalData = S::GlobalData;
+    type CaseData = CD;
+    type Solution = S::Solution;
+
+    fn solve_case(&self, global_data: &S::GlobalData, case_data: CD) -> S::Solution {
+        self.underlying.solve_case(global_data, (self.map_fn)(case_data))
+    }
+}
+
+/// A solver which passes the index of the case being solved. It panics if it
+/// is called outside an executor; see `Solver::with_case_index`.
+#[derive(Debug)]
+pub struct WithCaseIndex<S> {
+    underlying: S,
+}
+
+impl<CD, S: Solver<CaseData = (CaseIndex, CD)>> Solver for WithCaseIndex<S> {
+    type GlobalData = S::GlobalData;
+    type CaseData = CD;
+    type Solution = S::Solution;
+
+    fn solve_case(&self, global_data: &S::GlobalData, case_data: CD) -> S::Solution {
+        let case = context::current_case()
+            .expect("with_case_index solver was called outside of an executor; use executor::solve_case");
+
+        self.underlying.solve_case(global_data, (case, case_data))
+    }
+}
+
+#[derive(Debug)]
+pub struct WithCancellation<S> {
+    underlying: S,
+}
+
+impl<CD, S: Solver<CaseData = (Cancellation, CD)>> Solver for WithCancellation<S> {
+    type GlobalData = S::GlobalData;
+    type CaseData = CD;
+    type Solution = S::Solution;
+
+    fn solve_case(&self, global_data: &S::GlobalData, case_data: CD) -> S::Solution {
+        self.underlying.solve_case(global_data, (Cancellation::current(), case_data))
+    }
+}
+
+#[derive(Debug)]
+pub struct InspectInput<S, F> {
+    underlying: S,
+    inspect_fn: F,
+}
+
+impl<S: Solver, F: Fn(&S::CaseData)> Solver for InspectInput<S, F> {
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = S::Solution;
+
+    fn solve_case(&self, global_data: &S::GlobalData, case_data: S::CaseData) -> S::Solution {
+        (self.inspect_fn)(&case_data);
+        self.underlying.solve_case(global_data, case_data)
+    }
+}
+
+#[derive(Debug)]
+pub struct Inspect<S, F> {
+    underlying: S,
+    inspect_fn: F,
+}
+
+impl<S: Solver, F: Fn(&S::Solution)> Solver for Inspect<S, F> {
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = S::Solution;
+
+    fn solve_case(&self, global_data: &S::GlobalData, case_data: S::CaseData) -> S::Solution {
+        let solution = self.underlying.solve_case(global_data, case_data);
+        (self.inspect_fn)(&solution);
+        solution
+    }
 }
 
 pub struct FnSolver<F: Fn(CD) -> S, CD, S> {
@@ -47,10 +222,111 @@ impl<GD, CD, S, F: Fn(&GD, CD) -> S> Solver for GlobalFnSolver<F, GD, CD, S> {
     }
 }
 
+/// Per-worker solver states. Each thread gets its own state the first time
+/// it solves a case, and reuses it for every later case, so a state is never
+/// shared between cases solved at the same time.
+#[derive(Debug)]
+struct StatePool<St: Send, N: Fn() -> St> {
+    new_state: N,
+    states: ThreadLocal<RefCell<St>>,
+}
+
+impl<St: Send, N: Fn() -> St> StatePool<St, N> {
+    fn new(new_state: N) -> Self {
+        StatePool {
+            new_state,
+            states: ThreadLocal::new(),
+        }
+    }
+
+    fn with_state<R>(&self, body: impl FnOnce(&mut St) -> R) -> R {
+        let state = self
+            .states
+            .get_or(|| Box::new(RefCell::new((self.new_state)())));
+
+        match state.try_borrow_mut() {
+            Ok(mut state) => body(&mut state),
+            // The solver was called from inside itself; give the inner call
+            // a fresh state rather than aliasing the outer one.
+            Err(..) => body(&mut (self.new_state)()),
+        }
+    }
+}
+
 #[derive(Debug)]
+pub struct StatefulFnSolver<N: Fn() -> St, F: Fn(&mut St, CD) -> S, St: Send, CD, S> {
+    solver_fn: F,
+    pool: StatePool<St, N>,
+    case_phantom: PhantomData<CD>,
+    solution_phantom: PhantomData<S>,
+}
+
+impl<St: Send, CD, S, N: Fn() -> St, F: Fn(&mut St, CD) -> S> Solver for StatefulFnSolver<N, F, St, CD, S> {
+    type GlobalData = ();
+    type CaseData = CD;
+    type Solution = S;
+
+    fn solve_case(&self, _global: &(), case_data: CD) -> S {
+        self.pool.with_state(move |state| (self.solver_fn)(state, case_data))
+    }
+}
+
+#[derive(Debug)]
+pub struct GlobalStatefulFnSolver<N: Fn() -> St, F: Fn(&mut St, &GD, CD) -> S, St: Send, GD, CD, S> {
+    solver_fn: F,
+    pool: StatePool<St, N>,
+    global_phantom: PhantomData<GD>,
+    case_phantom: PhantomData<CD>,
+    solution_phantom: PhantomData<S>,
+}
+
+impl<St: Send, GD, CD, S, N: Fn() -> St, F: Fn(&mut St, &GD, CD) -> S> Solver
+    for GlobalStatefulFnSolver<N, F, St, GD, CD, S>
+{
+    type GlobalData = GD;
+    type CaseData = CD;
+    type Solution = S;
+
+    fn solve_case(&self, global_data: &GD, case_data: CD) -> S {
+        self.pool
+            .with_state(move |state| (self.solver_fn)(state, global_data, case_data))
+    }
+}
+
+/// The usual "no solution" token for Code Jam problems
+pub const IMPOSSIBLE: &str = "IMPOSSIBLE";
+
+/// The "no solution" token for problems which expect a number
+pub const NEGATIVE_ONE: &str = "-1";
+
+/// The "no solution" token for yes / no problems
+pub const NO: &str = "NO";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum MaybeImpossibleSolution<T> {
     Success(T),
-    Failure(&'static str),
+    Failure(Cow<'static, str>),
+}
+
+impl<T> MaybeImpossibleSolution<T> {
+    pub fn failure(message: impl Into<Cow<'static, str>>) -> Self {
+        MaybeImpossibleSolution::Failure(message.into())
+    }
+
+    pub fn impossible() -> Self {
+        Self::failure(IMPOSSIBLE)
+    }
+}
+
+impl<T, E: Display> From<Result<T, E>> for MaybeImpossibleSolution<T> {
+    fn from(result: Result<T, E>) -> Self {
+        use self::MaybeImpossibleSolution::*;
+
+        match result {
+            Ok(solution) => Success(solution),
+            Err(err) => Failure(err.to_string().into()),
+        }
+    }
 }
 
 impl<T: Display> Display for MaybeImpossibleSolution<T> {
@@ -67,7 +343,7 @@ impl<T: Display> Display for MaybeImpossibleSolution<T> {
 #[derive(Debug)]
 pub struct MaybeImpossibleSolver<S, T: Solver<Solution = Option<S>>> {
     underlying: T,
-    message: &'static str,
+    message: Cow<'static, str>,
 }
 
 impl<S, T: Solver<Solution = Option<S>>> Solver for MaybeImpossibleSolver<S, T> {
@@ -84,22 +360,71 @@ impl<S, T: Solver<Solution = Option<S>>> Solver for MaybeImpossibleSolver<S, T>
 
         match self.underlying.solve_case(global_data, case_data) {
             Some(solution) => Success(solution),
-            None => Failure(self.message),
+            None => Failure(self.message.clone()),
         }
     }
 }
 
 pub trait IntoMaybeSolver<T>: Solver<Solution = Option<T>> + Sized {
-    fn or_else(self, message: &'static str) -> MaybeImpossibleSolver<T, Self> {
+    fn or_else(self, message: impl Into<Cow<'static, str>>) -> MaybeImpossibleSolver<T, Self> {
         MaybeImpossibleSolver {
             underlying: self,
-            message,
+            message: message.into(),
         }
     }
+
+    fn or_impossible(self) -> MaybeImpossibleSolver<T, Self> {
+        self.or_else(IMPOSSIBLE)
+    }
 }
 
 impl<T, S> IntoMaybeSolver<S> for T where T: Solver<Solution = Option<S>> {}
 
+#[derive(Debug)]
+pub struct ResultSolver<S> {
+    underlying: S,
+}
+
+impl<T, E: Display, S: Solver<Solution = Result<T, E>>> Solver for ResultSolver<S> {
+    type GlobalData = S::GlobalData;
+    type CaseData = S::CaseData;
+    type Solution = MaybeImpossibleSolution<T>;
+
+    fn solve_case(
+        &self,
+        global_data: &Self::GlobalData,
+        case_data: Self::CaseData,
+    ) -> Self::Solution {
+        self.underlying.solve_case(global_data, case_data).into()
+    }
+}
+
+type ResultToOption<T, E> = fn(Result<T, E>) -> Option<T>;
+
+pub trait IntoResultSolver<T, E>: Solver<Solution = Result<T, E>> + Sized {
+    /// Print the error message for failed cases
+    fn or_error(self) -> ResultSolver<Self>
+        where E: Display
+    {
+        ResultSolver { underlying: self }
+    }
+
+    /// Print a fixed message for failed cases, ignoring the error
+    fn or_else(
+        self,
+        message: impl Into<Cow<'static, str>>,
+    ) -> MaybeImpossibleSolver<T, MapSolution<Self, ResultToOption<T, E>>> {
+        let ok: ResultToOption<T, E> = Result::ok;
+        self.map(ok).or_else(message)
+    }
+
+    fn or_impossible(self) -> MaybeImpossibleSolver<T, MapSolution<Self, ResultToOption<T, E>>> {
+        IntoResultSolver::or_else(self, IMPOSSIBLE)
+    }
+}
+
+impl<T, E, S> IntoResultSolver<T, E> for S where S: Solver<Solution = Result<T, E>> {}
+
 pub fn solver<CD, S, F: Fn(CD) -> S>(solver_fn: F) -> FnSolver<F, CD, S> {
     FnSolver {
         solver_fn,
@@ -116,3 +441,164 @@ pub fn global_solver<GD, CD, S, F: Fn(&GD, CD) -> S>(solver_fn: F) -> GlobalFnSo
         solution_phantom: PhantomData,
     }
 }
+
+/// Create a solver with mutable scratch space. `new_state` is called to
+/// create a state for each worker thread; states are reused between cases,
+/// so solvers should reset whatever parts of the state they depend on.
+pub fn stateful_solver<St: Send, CD, S, N: Fn() -> St, F: Fn(&mut St, CD) -> S>(
+    new_state: N,
+    solver_fn: F,
+) -> StatefulFnSolver<N, F, St, CD, S> {
+    StatefulFnSolver {
+        solver_fn,
+        pool: StatePool::new(new_state),
+        case_phantom: PhantomData,
+        solution_phantom: PhantomData,
+    }
+}
+
+pub fn global_stateful_solver<St: Send, GD, CD, S, N: Fn() -> St, F: Fn(&mut St, &GD, CD) -> S>(
+    new_state: N,
+    solver_fn: F,
+) -> GlobalStatefulFnSolver<N, F, St, GD, CD, S> {
+    GlobalStatefulFnSolver {
+        solver_fn,
+        pool: StatePool::new(new_state),
+        global_phantom: PhantomData,
+        case_phantom: PhantomData,
+        solution_phantom: PhantomData,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread::{self, ThreadId};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::executor::{self, Executor, SequentialExecutor, ThreadExecutor};
+    use crate::printer::CollectingPrinter;
+    use crate::tokens::TokensReader;
+
+    const INPUT: &str = "12 1 2 3 4 5 6 7 8 9 10 11 12";
+
+    #[test]
+    fn with_case_index_uses_the_case_context() {
+        let solver = solver(|(case, n): (CaseIndex, usize)| case.number() * n).with_case_index();
+        let case = CaseIndex::new(3).unwrap();
+
+        assert_eq!(executor::solve_case(&solver, case, &(), 5), 15);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of an executor")]
+    fn with_case_index_panics_without_a_context() {
+        let solver = solver(|(case, n): (CaseIndex, usize)| case.number() * n).with_case_index();
+
+        solver.solve_case(&(), 5);
+    }
+
+    #[test]
+    fn stateful_solver_reuses_one_state_sequentially() {
+        let created = AtomicUsize::new(0);
+        let solver = stateful_solver(
+            || created.fetch_add(1, Ordering::SeqCst),
+            |_state: &mut usize, n: u32| n * 2,
+        );
+
+        let mut printer = CollectingPrinter::new();
+        SequentialExecutor::new().run(TokensReader::new(INPUT.as_bytes()), &mut printer, solver);
+
+        assert_eq!(printer.solutions().len(), 12);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn stateful_solver_keeps_states_per_worker() {
+        let created = AtomicUsize::new(0);
+        let owners: Mutex<HashMap<usize, ThreadId>> = Mutex::new(HashMap::new());
+
+        let solver = stateful_solver(
+            || (created.fetch_add(1, Ordering::SeqCst), false),
+            |state: &mut (usize, bool), n: u32| {
+                let (id, ref mut busy) = *state;
+                assert!(!*busy, "state {} used by two cases at once", id);
+                *busy = true;
+
+                let owner = *owners.lock().unwrap().entry(id).or_insert_with(|| thread::current().id());
+                assert_eq!(owner, thread::current().id(), "state {} moved between threads", id);
+
+                thread::sleep(Duration::from_millis(5));
+                *busy = false;
+                n
+            },
+        );
+
+        let mut printer = CollectingPrinter::new();
+        ThreadExecutor::with_threads(3).run(TokensReader::new(INPUT.as_bytes()), &mut printer, solver);
+
+        assert_eq!(printer.solutions().len(), 12);
+        assert!(created.load(Ordering::SeqCst) <= 3);
+    }
+
+    fn halve(n: u32) -> Result<u32, String> {
+        if n % 2 == 1 {
+            Err(format!("{} is odd", n))
+        } else {
+            Ok(n / 2)
+        }
+    }
+
+    fn printed<S>(solver: S) -> Vec<String>
+        where
+            S: Solver<GlobalData = (), CaseData = u32>,
+            S::Solution: Display + Send,
+    {
+        let mut printer = CollectingPrinter::new();
+        SequentialExecutor::new().run(TokensReader::new("4 1 2 3 4".as_bytes()), &mut printer, solver);
+
+        printer.into_solutions().into_iter().map(|(_, solution)| solution).collect()
+    }
+
+    #[test]
+    fn result_converts_into_maybe_impossible_solution() {
+        let success: MaybeImpossibleSolution<u32> = halve(4).into();
+        let failure: MaybeImpossibleSolution<u32> = halve(3).into();
+
+        assert_eq!(success, MaybeImpossibleSolution::Success(2));
+        assert_eq!(failure, MaybeImpossibleSolution::failure("3 is odd"));
+        assert_eq!(success.to_string(), "2");
+        assert_eq!(failure.to_string(), "3 is odd");
+    }
+
+    #[test]
+    fn option_solver_or_impossible_prints_impossible() {
+        let solver = solver(|n: u32| halve(n).ok()).or_impossible();
+
+        assert_eq!(printed(solver), ["IMPOSSIBLE", "1", "IMPOSSIBLE", "2"]);
+    }
+
+    #[test]
+    fn result_solver_or_error_prints_the_error() {
+        let solver = solver(halve).or_error();
+
+        assert_eq!(printed(solver), ["1 is odd", "1", "3 is odd", "2"]);
+    }
+
+    #[test]
+    fn result_solver_or_else_ignores_the_error() {
+        let solver = IntoResultSolver::or_else(solver(halve), NEGATIVE_ONE);
+
+        assert_eq!(printed(solver), ["-1", "1", "-1", "2"]);
+    }
+
+    #[test]
+    fn result_solver_or_impossible_prints_impossible() {
+        let solver = solver(halve).or_impossible();
+
+        assert_eq!(printed(solver), ["IMPOSSIBLE", "1", "IMPOSSIBLE", "2"]);
+    }
+}
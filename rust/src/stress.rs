@@ -0,0 +1,467 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+pub use rand::Rng;
+
+use crate::context;
+use crate::cross_check::{compare_solutions, solutions_equal, SolutionsEqual};
+use crate::data::{input_text, Group, LoadGlobalData, WriteGlobalData, WriteTokens};
+use crate::generate::{seeded, StdRng};
+use crate::solver::Solver;
+use crate::tokens::{Tokens, TokensReader};
+
+/// A failing input found by a stress test
+#[derive(Debug, Clone)]
+pub struct StressFailure {
+    pub seed: u64,
+    pub size: usize,
+    /// The complete input file, including the case count. Unless shrinking
+    /// is turned off, this is the generated input after shrinking.
+    pub input: String,
+    pub reason: String,
+}
+
+impl Display for StressFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "stress test failed with seed {} and size {}", self.seed, self.size)?;
+        writeln!(f, "{}", self.reason)?;
+        writeln!(f, "input file:")?;
+        write!(f, "{}", self.input)
+    }
+}
+
+/// Something a stress test generator can return, which is turned into a
+/// complete input file.
+pub trait StressInput {
+    fn input_file(&self) -> String;
+}
+
+/// The data of a single case is written as an input file with a case count
+/// of 1 and no global data.
+impl<C: WriteTokens> StressInput for C {
+    fn input_file(&self) -> String {
+        input_text(&(), &[self])
+    }
+}
+
+/// The data of a single case, along with the problem's global data, such as
+/// a `CountPrefix` or `CountSuffix`.
+#[derive(Debug, Clone)]
+pub struct WithGlobalData<G, C>(pub G, pub C);
+
+impl<G: WriteGlobalData, C: WriteTokens> StressInput for WithGlobalData<G, C> {
+    fn input_file(&self) -> String {
+        input_text(&self.0, &[&self.1])
+    }
+}
+
+/// The raw text of a complete input file, including the case count
+#[derive(Debug, Clone)]
+pub struct RawInput(pub String);
+
+impl StressInput for RawInput {
+    fn input_file(&self) -> String {
+        self.0.clone()
+    }
+}
+
+thread_local! {
+    static QUIET_PANICS: Cell<bool> = Cell::new(false);
+}
+
+static INSTALL_QUIET_HOOK: Once = Once::new();
+
+// Run a function which is expected to panic, catching the panic without the
+// panic hook printing a message. Swapping the hook for each call would race
+// with other threads, so a hook which stays silent on threads inside this
+// function is installed once, in front of the existing one.
+fn catch_quietly<T>(body: impl FnOnce() -> T) -> Result<T, Box<dyn Any + Send>> {
+    INSTALL_QUIET_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET_PANICS.with(Cell::get) {
+                hook(info)
+            }
+        }));
+    });
+
+    QUIET_PANICS.with(|quiet| quiet.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(body));
+    QUIET_PANICS.with(|quiet| quiet.set(false));
+
+    result
+}
+
+// How an input failed. Shrinking only keeps inputs which fail in the same
+// way, so that a mismatch can't shrink into an input which makes a solver
+// panic, or into one which can't be loaded at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailureKind {
+    Invalid,
+    Mismatch,
+    Panic,
+}
+
+// Integers closer to zero than `n`, from the furthest to the nearest
+fn smaller_integers(n: i64) -> Vec<i64> {
+    let mut smaller = vec![0, n / 2, n - n.signum()];
+    smaller.dedup();
+    smaller.retain(|&m| m != n);
+    smaller
+}
+
+fn join_lines(lines: &[Vec<String>]) -> String {
+    lines.iter().map(|line| line.join(" ") + "\n").collect()
+}
+
+// Every input one step smaller than the given one: without one of its
+// lines, without one of its tokens, or with one integer moved towards zero
+fn shrink_candidates(input: &str) -> Vec<String> {
+    let lines: Vec<Vec<String>> = input
+        .lines()
+        .map(|line| line.split_whitespace().map(str::to_string).collect())
+        .collect();
+
+    let mut candidates = Vec::new();
+
+    for i in 0..lines.len() {
+        let mut smaller = lines.clone();
+        smaller.remove(i);
+        candidates.push(join_lines(&smaller));
+    }
+
+    for (i, line) in lines.iter().enumerate().filter(|(_, line)| line.len() > 1) {
+        for j in 0..line.len() {
+            let mut smaller = lines.clone();
+            smaller[i].remove(j);
+            candidates.push(join_lines(&smaller));
+        }
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        for (j, token) in line.iter().enumerate() {
+            if let Ok(n) = token.parse::<i64>() {
+                for m in smaller_integers(n) {
+                    let mut smaller = lines.clone();
+                    smaller[i][j] = m.to_string();
+                    candidates.push(join_lines(&smaller));
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+// Describe a caught panic, using its message if it has one
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+    {
+        Some(message) => format!("a solver panicked: {}", message),
+        None => "a solver panicked".to_string(),
+    }
+}
+
+/// A stress test, which runs a solver and a reference solver over many
+/// random inputs and reports the smallest failing input it can find.
+///
+/// Once an input fails, smaller sizes are tried to find the smallest size at
+/// which the solvers disagree. The failing input is then shrunk by removing
+/// lines and tokens and by moving integers towards zero, for as long as it
+/// still fails in the same way.
+///
+/// The generator is called with a seeded RNG and a size, and returns a
+/// `StressInput`: the data of a single case, a `WithGlobalData`, or a
+/// `RawInput`.
+#[derive(Debug)]
+pub struct Stress<S, R, G, C> {
+    solver: S,
+    reference: R,
+    generate: G,
+    compare: C,
+    runs: usize,
+    max_size: usize,
+    seed: u64,
+    shrink: bool,
+}
+
+pub fn stress<S, R, G, I>(
+    solver: S,
+    reference: R,
+    generate: G,
+) -> Stress<S, R, G, SolutionsEqual<S::Solution, R::Solution>>
+    where
+        S: Solver,
+        R: Solver<GlobalData = S::GlobalData, CaseData = S::CaseData>,
+        S::Solution: PartialEq<R::Solution>,
+        G: Fn(&mut StdRng, usize) -> I,
+        I: StressInput,
+{
+    Stress {
+        solver,
+        reference,
+        generate,
+        compare: solutions_equal,
+        runs: 1000,
+        max_size: 10,
+        seed: 0,
+        shrink: true,
+    }
+}
+
+impl<S, R, G, C, I> Stress<S, R, G, C>
+    where
+        S: Solver,
+        R: Solver<GlobalData = S::GlobalData, CaseData = S::CaseData>,
+        S::GlobalData: LoadGlobalData,
+        S::CaseData: Group + Clone + Debug,
+        S::Solution: Debug,
+        R::Solution: Debug,
+        G: Fn(&mut StdRng, usize) -> I,
+        I: StressInput,
+        C: Fn(&S::Solution, &R::Solution) -> bool,
+{
+    /// Set the number of random inputs to try
+    pub fn runs(self, runs: usize) -> Self {
+        Stress { runs, ..self }
+    }
+
+    /// Set the largest size passed to the generator. Sizes cycle from 1 up to
+    /// this value over the course of the runs.
+    pub fn max_size(self, max_size: usize) -> Self {
+        Stress {
+            max_size: max_size.max(1),
+            ..self
+        }
+    }
+
+    /// Set the seed of the first run; each run uses the next seed
+    pub fn seed(self, seed: u64) -> Self {
+        Stress { seed, ..self }
+    }
+
+    /// Set whether to shrink the failing input. Shrinking can produce inputs
+    /// which the generator never would, such as a 0 where the problem
+    /// promises a positive number, so turn it off if the solvers may
+    /// disagree on inputs outside of the problem's limits.
+    pub fn shrink(self, shrink: bool) -> Self {
+        Stress { shrink, ..self }
+    }
+
+    /// Compare solutions with a custom function instead of `==`
+    pub fn compare_by<C2>(self, compare: C2) -> Stress<S, R, G, C2>
+        where C2: Fn(&S::Solution, &R::Solution) -> bool
+    {
+        Stress {
+            solver: self.solver,
+            reference: self.reference,
+            generate: self.generate,
+            compare,
+            runs: self.runs,
+            max_size: self.max_size,
+            seed: self.seed,
+            shrink: self.shrink,
+        }
+    }
+
+    // Check the solvers against each other on every case of an input file
+    fn check_input(&self, input: &str) -> Result<(), (FailureKind, String)> {
+        let invalid = |err: &dyn Display| (FailureKind::Invalid, format!("generated input is invalid: {}", err));
+
+        let mut tokens = TokensReader::new(input.as_bytes());
+        let global_data = tokens
+            .start_problem::<S::GlobalData>()
+            .map_err(|err| invalid(&err))?;
+
+        let result = global_data.cases().try_for_each(|(case, _)| {
+            let case_data = tokens
+                .next::<S::CaseData>()
+                .map_err(|err| invalid(&err))?;
+
+            // Panics are reported as failures, rather than printing a message
+            // for each one.
+            let result = catch_quietly(|| {
+                let _context = context::enter_case(case);
+                compare_solutions(
+                    &self.solver,
+                    &self.reference,
+                    &self.compare,
+                    &global_data.data,
+                    case_data,
+                ).map(|_| ())
+            });
+
+            match result {
+                Ok(Ok(())) => Ok(()),
+                Ok(Err(mismatch)) => Err((FailureKind::Mismatch, mismatch.to_string())),
+                Err(payload) => Err((FailureKind::Panic, panic_message(&*payload))),
+            }
+        });
+
+        result
+    }
+
+    fn try_input(&self, seed: u64, size: usize) -> Result<(), StressFailure> {
+        let mut rng = seeded(seed);
+        let input = (self.generate)(&mut rng, size).input_file();
+
+        self.check_input(&input).map_err(move |(_, reason)| StressFailure {
+            seed,
+            size,
+            input,
+            reason,
+        })
+    }
+
+    // Look for a failure at each size smaller than the given failure, giving
+    // each size as many attempts as it had in the original runs
+    fn smallest_failing_size(&self, failure: StressFailure) -> StressFailure {
+        let attempts = (self.runs / self.max_size).max(1) as u64;
+        let base_seed = self.seed.wrapping_add(self.runs as u64);
+
+        (1..failure.size)
+            .flat_map(|size| {
+                (0..attempts).map(move |attempt| {
+                    let seed = base_seed.wrapping_add(size as u64 * attempts + attempt);
+                    (seed, size)
+                })
+            })
+            .filter_map(|(seed, size)| self.try_input(seed, size).err())
+            .next()
+            .unwrap_or(failure)
+    }
+
+    // Shrink a failing input one step at a time, taking the first smaller
+    // input which still fails in the same way, until none of them do
+    fn shrink_input(&self, mut failure: StressFailure) -> StressFailure {
+        let kind = match self.check_input(&failure.input) {
+            Err((kind, _)) if kind != FailureKind::Invalid => kind,
+            _ => return failure,
+        };
+
+        loop {
+            let smaller = shrink_candidates(&failure.input)
+                .into_iter()
+                .find_map(|input| match self.check_input(&input) {
+                    Err((candidate_kind, reason)) if candidate_kind == kind => Some((input, reason)),
+                    _ => None,
+                });
+
+            match smaller {
+                Some((input, reason)) => {
+                    failure.input = input;
+                    failure.reason = reason;
+                }
+                None => return failure,
+            }
+        }
+    }
+
+    /// Run the stress test, returning the smallest failing input found
+    pub fn check(&self) -> Result<(), StressFailure> {
+        (0..self.runs)
+            .map(|run| {
+                let seed = self.seed.wrapping_add(run as u64);
+                let size = run % self.max_size + 1;
+                (seed, size)
+            })
+            .try_for_each(|(seed, size)| self.try_input(seed, size))
+            .map_err(|failure| {
+                let failure = self.smallest_failing_size(failure);
+
+                if self.shrink {
+                    self.shrink_input(failure)
+                } else {
+                    failure
+                }
+            })
+    }
+
+    /// Run the stress test, panicking with the smallest failing input found
+    pub fn run(&self) {
+        self.check().unwrap_or_else(|failure| panic!("{}", failure))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::CountSuffix;
+    use crate::solver::{global_solver, solver};
+
+    #[test]
+    fn case_data_is_written_as_an_input_file() {
+        let failure = stress(
+            solver(|(a, b): (u32, u32)| a + b),
+            solver(|(a, b): (u32, u32)| if a > 2 { 0 } else { a + b }),
+            |rng: &mut StdRng, size| (rng.gen_range(0, size as u32 + 1), 1u32),
+        ).max_size(8).check().unwrap_err();
+
+        assert_eq!(failure.size, 3);
+        assert_eq!(failure.input, "1\n3 0\n");
+    }
+
+    #[test]
+    fn failing_input_is_shrunk_to_the_failing_case() {
+        let failure = stress(
+            solver(|n: i32| n),
+            solver(|n: i32| if n > 6 { panic!("too big") } else { n }),
+            |_rng: &mut StdRng, _size| RawInput("4\n1\n-3\n  17 \n2\n".to_string()),
+        ).runs(1).check().unwrap_err();
+
+        assert_eq!(failure.input, "1\n7\n");
+        assert_eq!(failure.reason, "a solver panicked: too big");
+    }
+
+    #[test]
+    fn shrinking_keeps_the_kind_of_failure() {
+        // Shrinking 5 to 0 would make the reference panic instead of
+        // disagreeing, so the input is only shrunk as far as 1.
+        let failure = stress(
+            solver(|n: u32| n * 2),
+            solver(|n: u32| 10 / n),
+            |_rng: &mut StdRng, _size| 5u32,
+        ).runs(1).check().unwrap_err();
+
+        assert_eq!(failure.input, "1\n1\n");
+    }
+
+    #[test]
+    fn shrinking_can_be_turned_off() {
+        let failure = stress(
+            solver(|n: u32| n),
+            solver(|n: u32| n + 1),
+            |_rng: &mut StdRng, _size| RawInput("2\n10 20\n".to_string()),
+        ).runs(1).shrink(false).check().unwrap_err();
+
+        assert_eq!(failure.input, "2\n10 20\n");
+    }
+
+    #[test]
+    fn global_data_is_written_in_its_own_layout() {
+        let solve = |&CountSuffix(offset): &CountSuffix<u32>, n: u32| n + offset;
+
+        stress(
+            global_solver(solve),
+            global_solver(solve),
+            |rng: &mut StdRng, _size| WithGlobalData(CountSuffix(rng.gen_range(0u32, 10)), 5u32),
+        ).runs(20).run();
+    }
+
+    #[test]
+    fn raw_input_checks_every_case() {
+        let failure = stress(
+            solver(|n: u32| n),
+            solver(|n: u32| if n == 7 { panic!("unlucky") } else { n }),
+            |_rng: &mut StdRng, _size| RawInput("3\n1\n7\n2\n".to_string()),
+        ).runs(1).check().unwrap_err();
+
+        assert_eq!(failure.reason, "a solver panicked: unlucky");
+    }
+}
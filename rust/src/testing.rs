@@ -0,0 +1,160 @@
+use std::fmt::{Display, Write};
+
+use crate::case_index::CaseIndex;
+use crate::data::{Group, LoadGlobalData};
+use crate::executor::{Executor, SequentialExecutor};
+use crate::printer::CollectingPrinter;
+use crate::solver::Solver;
+use crate::tokens::TokensReader;
+
+/// Split `Case #x: ...` output into (label, solution) pairs. A solution runs
+/// until the next line starting with `Case #`, so multi-line solutions are
+/// supported. Trailing whitespace is ignored.
+fn split_cases(output: &str) -> Vec<(String, String)> {
+    let mut cases: Vec<(String, Vec<&str>)> = Vec::new();
+
+    for line in output.lines().map(str::trim_end) {
+        if line.starts_with("Case #") {
+            let (label, rest) = match line.find(':') {
+                Some(colon) => (&line[..colon], line[colon + 1..].trim_start()),
+                None => (line, ""),
+            };
+            cases.push((label.to_string(), vec![rest]));
+        } else if let Some((_, lines)) = cases.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    cases
+        .into_iter()
+        .map(|(label, lines)| (label, lines.join("\n").trim().to_string()))
+        .collect()
+}
+
+fn indented(solution: &str) -> String {
+    solution
+        .lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Compare actual and expected output case by case, returning a report of
+/// every case that differs.
+pub fn compare_output(actual: &str, expected: &str) -> Result<(), String> {
+    compare_cases(&split_cases(actual), &split_cases(expected))
+}
+
+fn compare_cases(actual: &[(String, String)], expected: &[(String, String)]) -> Result<(), String> {
+    let mut report = String::new();
+
+    for (index, (label, expected_solution)) in expected.iter().enumerate() {
+        match actual.get(index) {
+            Some((actual_label, actual_solution))
+                if actual_label == label && actual_solution == expected_solution => {}
+            Some((actual_label, actual_solution)) => {
+                writeln!(report, "{}:", label).unwrap();
+                writeln!(report, "  expected:\n{}", indented(expected_solution)).unwrap();
+                if actual_label == label {
+                    writeln!(report, "  actual:\n{}", indented(actual_solution)).unwrap();
+                } else {
+                    writeln!(report, "  actual ({}):\n{}", actual_label, indented(actual_solution)).unwrap();
+                }
+            }
+            None => writeln!(report, "{}: missing from output", label).unwrap(),
+        }
+    }
+
+    for (label, _) in actual.iter().skip(expected.len()) {
+        writeln!(report, "{}: unexpected extra case", label).unwrap();
+    }
+
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Run a solver on sample input, returning its solutions
+pub fn run_samples<S: Solver>(solver: S, input: &str) -> Vec<(CaseIndex, String)>
+    where
+        S::GlobalData: LoadGlobalData,
+        S::CaseData: Group,
+        S::Solution: Display,
+{
+    let mut printer = CollectingPrinter::new();
+
+    SequentialExecutor::new()
+        .execute(TokensReader::new(input.as_bytes()), &mut printer, solver)
+        .unwrap_or_else(|err| panic!("error running samples: {}", err));
+
+    printer.into_solutions()
+}
+
+/// Run a solver on the sample input from a problem statement and assert that
+/// it produces the sample output.
+///
+/// ```ignore
+/// #[test]
+/// fn samples() {
+///     check_samples(solver(solve), include_str!("sample.in"), include_str!("sample.out"));
+/// }
+/// ```
+pub fn check_samples<S: Solver>(solver: S, input: &str, expected: &str)
+    where
+        S::GlobalData: LoadGlobalData,
+        S::CaseData: Group,
+        S::Solution: Display,
+{
+    // Lay out the solutions as an output file, so that they're normalised
+    // the same way as the expected output.
+    let mut actual = String::new();
+    for (case, solution) in run_samples(solver, input) {
+        writeln!(actual, "{}: {}", case, solution).unwrap();
+    }
+
+    if let Err(report) = compare_output(&actual, expected) {
+        panic!("output didn't match the samples:\n{}", report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solver;
+
+    const INPUT: &str = "2\n1 2\n3 4\n";
+
+    #[test]
+    fn check_samples_accepts_matching_output() {
+        check_samples(solver(|(a, b): (u32, u32)| a + b), INPUT, "Case #1: 3\nCase #2: 7\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "Case #2:\n  expected:\n    8\n  actual:\n    7")]
+    fn check_samples_reports_wrong_cases() {
+        check_samples(solver(|(a, b): (u32, u32)| a + b), INPUT, "Case #1: 3\nCase #2: 8\n");
+    }
+
+    #[test]
+    fn check_samples_normalises_multiline_solutions() {
+        let solve = |n: u32| format!("\n  {} \n{}  ", n, n + 1);
+
+        check_samples(solver(solve), "1 5", "Case #1:\n5\n6\n");
+    }
+
+    #[test]
+    fn compare_output_reports_missing_and_extra_cases() {
+        let report = compare_output("Case #1: 3\n", "Case #1: 3\nCase #2: 7\n").unwrap_err();
+        assert_eq!(report, "Case #2: missing from output\n");
+
+        let report = compare_output("Case #1: 3\nCase #2: 7\n", "Case #1: 3\n").unwrap_err();
+        assert_eq!(report, "Case #2: unexpected extra case\n");
+    }
+
+    #[test]
+    fn compare_output_ignores_trailing_whitespace() {
+        assert_eq!(compare_output("Case #1: 3  \nCase #2: 7", "Case #1: 3\nCase #2: 7\n\n"), Ok(()));
+    }
+}
@@ -0,0 +1,312 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::case_index::CaseIndex;
+use crate::monitor::Monitor;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaseTiming {
+    pub load_time: Duration,
+    pub solve_time: Duration,
+    /// Whether the case took longer than its time limit
+    pub timed_out: bool,
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+/// The load and solve times of every case in a run
+#[derive(Debug, Clone, Default)]
+pub struct TimingReport {
+    cases: Vec<(CaseIndex, CaseTiming)>,
+    slowest: usize,
+}
+
+impl TimingReport {
+    /// Every case's timings, in case order
+    pub fn cases(&self) -> &[(CaseIndex, CaseTiming)] {
+        &self.cases
+    }
+
+    pub fn total_load_time(&self) -> Duration {
+        self.cases.iter().map(|(_, timing)| timing.load_time).sum()
+    }
+
+    pub fn total_solve_time(&self) -> Duration {
+        self.cases.iter().map(|(_, timing)| timing.solve_time).sum()
+    }
+
+    pub fn mean_solve_time(&self) -> Duration {
+        match self.cases.len() {
+            0 => Duration::default(),
+            len => self.total_solve_time().div_f64(len as f64),
+        }
+    }
+
+    /// The solve time which `percentile` percent of cases finish within
+    pub fn percentile_solve_time(&self, percentile: f64) -> Duration {
+        let mut times: Vec<Duration> = self.cases.iter().map(|(_, timing)| timing.solve_time).collect();
+        times.sort();
+
+        if times.is_empty() {
+            return Duration::default();
+        }
+
+        let rank = (percentile / 100.0 * times.len() as f64).ceil() as usize;
+        times[rank.max(1).min(times.len()) - 1]
+    }
+
+    /// The `count` cases which took longest to solve, slowest first
+    pub fn slowest(&self, count: usize) -> Vec<(CaseIndex, CaseTiming)> {
+        let mut cases = self.cases.clone();
+        cases.sort_by_key(|(_, timing)| Reverse(timing.solve_time));
+        cases.truncate(count);
+        cases
+    }
+
+    /// The cases which took longer than their time limit
+    pub fn timed_out(&self) -> Vec<CaseIndex> {
+        self.cases
+            .iter()
+            .filter(|(_, timing)| timing.timed_out)
+            .map(|&(case, _)| case)
+            .collect()
+    }
+
+    /// Write every case's timings as CSV, with times in seconds
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "case,load_seconds,solve_seconds,timed_out")?;
+
+        self.cases.iter().try_for_each(|(case, timing)| {
+            writeln!(
+                writer,
+                "{},{:.9},{:.9},{}",
+                case.number(),
+                seconds(timing.load_time),
+                seconds(timing.solve_time),
+                timing.timed_out
+            )
+        })
+    }
+}
+
+/// A summary of the report: totals, mean, p99 and the slowest cases
+impl Display for TimingReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "timing for {} cases:", self.cases.len())?;
+        writeln!(f, "  total load:  {:.3}s", seconds(self.total_load_time()))?;
+        writeln!(f, "  total solve: {:.3}s", seconds(self.total_solve_time()))?;
+        writeln!(f, "  mean solve:  {:.3}s", seconds(self.mean_solve_time()))?;
+        writeln!(f, "  p99 solve:   {:.3}s", seconds(self.percentile_solve_time(99.0)))?;
+
+        let timed_out = self.timed_out();
+        if !timed_out.is_empty() {
+            let cases: Vec<String> = timed_out.iter().map(|case| case.number().to_string()).collect();
+            writeln!(f, "  timed out:   {} cases (#{})", timed_out.len(), cases.join(", #"))?;
+        }
+
+        write!(f, "  slowest cases:")?;
+
+        self.slowest(self.slowest).iter().try_for_each(|(case, timing)| {
+            write!(
+                f,
+                "\n    {}: solve {:.3}s, load {:.3}s{}",
+                case,
+                seconds(timing.solve_time),
+                seconds(timing.load_time),
+                if timing.timed_out { " (timed out)" } else { "" }
+            )
+        })
+    }
+}
+
+/// A monitor which records the load and solve time of each case. When the run
+/// finishes, a summary is printed to stderr, or the full timings are written
+/// as CSV to a file.
+#[derive(Debug)]
+pub struct Timings {
+    cases: Mutex<HashMap<CaseIndex, CaseTiming>>,
+    slowest: usize,
+    csv_path: Option<PathBuf>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Timings {
+            cases: Mutex::new(HashMap::new()),
+            slowest: 5,
+            csv_path: None,
+        }
+    }
+
+    /// Set how many of the slowest cases are listed in the summary
+    pub fn slowest(self, slowest: usize) -> Self {
+        Timings { slowest, ..self }
+    }
+
+    /// Write the timings as CSV to a file, instead of printing a summary
+    pub fn csv_file(self, path: impl Into<PathBuf>) -> Self {
+        Timings {
+            csv_path: Some(path.into()),
+            ..self
+        }
+    }
+
+    pub fn report(&self) -> TimingReport {
+        let mut cases: Vec<_> = self
+            .cases
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&case, &timing)| (case, timing))
+            .collect();
+        cases.sort_by_key(|&(case, _)| case);
+
+        TimingReport {
+            cases,
+            slowest: self.slowest,
+        }
+    }
+
+    fn update(&self, case: CaseIndex, update: impl FnOnce(&mut CaseTiming)) {
+        update(self.cases.lock().unwrap().entry(case).or_default())
+    }
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor for Timings {
+    fn case_loaded(&self, case: CaseIndex, load_time: Duration) {
+        self.update(case, |timing| timing.load_time = load_time)
+    }
+
+    fn case_solved(&self, case: CaseIndex, solve_time: Duration) {
+        self.update(case, |timing| timing.solve_time = solve_time)
+    }
+
+    fn case_timed_out(&self, case: CaseIndex, _solve_time: Duration) {
+        self.update(case, |timing| timing.timed_out = true)
+    }
+
+    fn finish(&self) {
+        let report = self.report();
+
+        match self.csv_path {
+            None => eprintln!("{}", report),
+            Some(ref path) => {
+                let result = File::create(path).and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    report.write_csv(&mut writer)?;
+                    writer.flush()
+                });
+
+                if let Err(err) = result {
+                    eprintln!("error writing timings to {}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(number: usize) -> CaseIndex {
+        CaseIndex::new(number).unwrap()
+    }
+
+    // A report of cases whose solve times are the given numbers of
+    // milliseconds, each taking 1ms to load
+    fn report(solve_millis: &[u64]) -> TimingReport {
+        let timings = Timings::new().slowest(2);
+
+        for (i, &millis) in solve_millis.iter().enumerate() {
+            timings.case_loaded(case(i + 1), Duration::from_millis(1));
+            timings.case_solved(case(i + 1), Duration::from_millis(millis));
+        }
+
+        timings.report()
+    }
+
+    #[test]
+    fn percentiles_pick_the_case_at_that_rank() {
+        let report = report(&[40, 10, 30, 20, 50]);
+
+        assert_eq!(report.percentile_solve_time(0.0), Duration::from_millis(10));
+        assert_eq!(report.percentile_solve_time(50.0), Duration::from_millis(30));
+        assert_eq!(report.percentile_solve_time(100.0), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn percentiles_of_a_single_case_are_that_case() {
+        let report = report(&[7]);
+
+        assert_eq!(report.percentile_solve_time(0.0), Duration::from_millis(7));
+        assert_eq!(report.percentile_solve_time(50.0), Duration::from_millis(7));
+        assert_eq!(report.percentile_solve_time(100.0), Duration::from_millis(7));
+        assert_eq!(TimingReport::default().percentile_solve_time(50.0), Duration::default());
+    }
+
+    #[test]
+    fn slowest_cases_come_first() {
+        let report = report(&[40, 10, 30]);
+        let slowest: Vec<CaseIndex> = report.slowest(2).iter().map(|&(case, _)| case).collect();
+
+        assert_eq!(slowest, [case(1), case(3)]);
+        assert_eq!(report.slowest(10).len(), 3);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_case() {
+        let timings = Timings::new();
+        timings.case_loaded(case(2), Duration::from_millis(1));
+        timings.case_solved(case(2), Duration::from_millis(2500));
+        timings.case_timed_out(case(2), Duration::from_millis(2500));
+        timings.case_solved(case(1), Duration::from_micros(5));
+
+        let mut csv = Vec::new();
+        timings.report().write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "case,load_seconds,solve_seconds,timed_out\n\
+             1,0.000000000,0.000005000,false\n\
+             2,0.001000000,2.500000000,true\n",
+        );
+    }
+
+    #[test]
+    fn summary_lists_totals_and_the_slowest_cases() {
+        let timings = Timings::new().slowest(2);
+        for &(number, millis) in &[(1, 100), (2, 300), (3, 200)] {
+            timings.case_loaded(case(number), Duration::from_millis(10));
+            timings.case_solved(case(number), Duration::from_millis(millis));
+        }
+        timings.case_timed_out(case(2), Duration::from_millis(300));
+
+        assert_eq!(
+            timings.report().to_string(),
+            "timing for 3 cases:\n\
+             \x20 total load:  0.030s\n\
+             \x20 total solve: 0.600s\n\
+             \x20 mean solve:  0.200s\n\
+             \x20 p99 solve:   0.300s\n\
+             \x20 timed out:   1 cases (#2)\n\
+             \x20 slowest cases:\n\
+             \x20   Case #2: solve 0.300s, load 0.010s (timed out)\n\
+             \x20   Case #3: solve 0.200s, load 0.010s",
+        );
+    }
+}